@@ -1,4 +1,4 @@
-use crate::{Kw, Lit, Punct, Token};
+use crate::{Kw, Lit, Pos, Punct, Span, Token};
 use anyhow::{bail, Result};
 use std::str::Chars;
 
@@ -6,6 +6,7 @@ use std::str::Chars;
 #[derive(Clone)]
 struct Cursor<'a> {
     idx: usize,
+    pos: Pos,
     top: Option<char>,
     chars: Chars<'a>,
 }
@@ -15,12 +16,17 @@ impl<'a> Cursor<'a> {
     pub fn new(input: &'a str) -> Self {
         let mut chars = input.chars();
         let top = chars.next();
-        Cursor { idx: 0, top, chars }
+        Cursor {
+            idx: 0,
+            pos: Pos { line: 1, col: 1 },
+            top,
+            chars,
+        }
     }
 
     /// Check whether there are any unconsumed chars remaining
     pub fn is_empty(&self) -> bool {
-        self.top == None
+        self.top.is_none()
     }
 
     /// Returns the next unconsumed char
@@ -33,6 +39,14 @@ impl<'a> Cursor<'a> {
         let output = self.top;
         self.top = self.chars.next();
         self.idx += 1;
+        match output {
+            Some('\n') => {
+                self.pos.line += 1;
+                self.pos.col = 1;
+            }
+            Some(_) => self.pos.col += 1,
+            None => {}
+        }
         output
     }
 
@@ -96,9 +110,18 @@ fn munch_keyword<'a>(cursor: &mut Cursor<'a>) -> Option<Token> {
         }
     }
 
+    // A keyword followed by more identifier characters is an identifier, e.g. `integer`
+    let mut new_cursor = cursor.clone();
+    new_cursor.advance(best_len);
+    if let Some(c) = new_cursor.peek() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            return None;
+        }
+    }
+
     match best_keyword {
         Some(keyword) => {
-            cursor.advance(best_len);
+            std::mem::swap(cursor, &mut new_cursor);
             Some(Token::Kw(keyword))
         }
         None => None,
@@ -173,7 +196,7 @@ fn munch_string_char(cursor: &mut Cursor) -> Option<char> {
 
 fn munch_literal_string<'a>(cursor: &mut Cursor<'a>) -> Option<Token> {
     match cursor.peek() {
-        Some(c) if c == '"' => {}
+        Some('"') => {}
         _ => return None,
     }
 
@@ -199,14 +222,14 @@ fn munch_literal_string<'a>(cursor: &mut Cursor<'a>) -> Option<Token> {
 
 fn munch_literal_integer<'a>(cursor: &mut Cursor<'a>) -> Option<Token> {
     match cursor.peek() {
-        Some(c) if ('0'..='9').contains(&c) => {}
+        Some(c) if c.is_ascii_digit() => {}
         _ => return None,
     }
 
     let mut value = 0;
     loop {
         match cursor.peek() {
-            Some(c) if ('0'..='9').contains(&c) => {
+            Some(c) if c.is_ascii_digit() => {
                 let digit = c as u64 - '0' as u64;
                 value = value * 10 + digit;
                 cursor.advance(1);
@@ -220,38 +243,68 @@ fn munch_literal_integer<'a>(cursor: &mut Cursor<'a>) -> Option<Token> {
 
 /// Perform lexical analysis, converts the input string into
 /// a sequence of tokens.
-pub fn lex<'a>(input: &'a str) -> Result<Vec<Token>> {
+pub fn lex(input: &str) -> Result<Vec<Token>> {
+    let (tokens, _) = lex_with_spans(input)?;
+    Ok(tokens)
+}
+
+/// Perform lexical analysis, returning the sequence of tokens along with
+/// the span of source text each token was lexed from.
+pub fn lex_with_spans(input: &str) -> Result<(Vec<Token>, Vec<Span>)> {
     let mut cursor = Cursor::new(input);
     let mut tokens = Vec::new();
+    let mut spans = Vec::new();
     while !cursor.is_empty() {
         // TODO: Munch comments
 
-        if let Some(_) = munch_whitespace(&mut cursor) {
+        if munch_whitespace(&mut cursor).is_some() {
             continue;
         }
 
+        let start = cursor.pos;
+
         if let Some(token) = munch_punctuation(&mut cursor) {
             tokens.push(token);
+            spans.push(Span {
+                start,
+                end: cursor.pos,
+            });
             continue;
         }
 
         if let Some(token) = munch_keyword(&mut cursor) {
             tokens.push(token);
+            spans.push(Span {
+                start,
+                end: cursor.pos,
+            });
             continue;
         }
 
         if let Some(token) = munch_identifier(&mut cursor) {
             tokens.push(token);
+            spans.push(Span {
+                start,
+                end: cursor.pos,
+            });
             continue;
         }
 
         if let Some(token) = munch_literal_string(&mut cursor) {
             tokens.push(token);
+            spans.push(Span {
+                start,
+                end: cursor.pos,
+            });
             continue;
         }
 
         if let Some(token) = munch_literal_integer(&mut cursor) {
             tokens.push(token);
+            spans.push(Span {
+                start,
+                end: cursor.pos,
+            });
             continue;
         }
 
@@ -262,5 +315,5 @@ pub fn lex<'a>(input: &'a str) -> Result<Vec<Token>> {
         let top = cursor.peek().unwrap();
         bail!("unable to tokenize at index {}: '{}'", cursor.idx, top);
     }
-    Ok(tokens)
+    Ok((tokens, spans))
 }
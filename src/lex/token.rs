@@ -1,10 +1,20 @@
-use crate::{indent, PrettyPrint};
-use std::fmt;
+use crate::{indent, PrettyPrint, PrettyWriter};
+use std::fmt::{self, Write};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Kw {
+    Alignas,
+    Alignof,
+    Asm,
+    Attribute,
     Auto,
+    Bool,
     Break,
+    BuiltinVaArg,
+    BuiltinVaCopy,
+    BuiltinVaEnd,
+    BuiltinVaList,
+    BuiltinVaStart,
     Case,
     Char,
     Const,
@@ -14,13 +24,17 @@ pub enum Kw {
     Double,
     Else,
     Enum,
+    Extension,
     Extern,
     Float,
     For,
+    Generic,
+    GnuTypeof,
     Goto,
     If,
     Inline,
     Int,
+    Int128,
     Long,
     Register,
     Restrict,
@@ -29,9 +43,11 @@ pub enum Kw {
     Signed,
     Sizeof,
     Static,
+    StaticAssert,
     Struct,
     Switch,
     Typedef,
+    Typeof,
     Union,
     Unsigned,
     Void,
@@ -41,8 +57,18 @@ pub enum Kw {
 
 impl Kw {
     pub const ALL: &[Kw] = &[
+        Kw::Alignas,
+        Kw::Alignof,
+        Kw::Asm,
+        Kw::Attribute,
         Kw::Auto,
+        Kw::Bool,
         Kw::Break,
+        Kw::BuiltinVaArg,
+        Kw::BuiltinVaCopy,
+        Kw::BuiltinVaEnd,
+        Kw::BuiltinVaList,
+        Kw::BuiltinVaStart,
         Kw::Case,
         Kw::Char,
         Kw::Const,
@@ -52,13 +78,17 @@ impl Kw {
         Kw::Double,
         Kw::Else,
         Kw::Enum,
+        Kw::Extension,
         Kw::Extern,
         Kw::Float,
         Kw::For,
+        Kw::Generic,
+        Kw::GnuTypeof,
         Kw::Goto,
         Kw::If,
         Kw::Inline,
         Kw::Int,
+        Kw::Int128,
         Kw::Long,
         Kw::Register,
         Kw::Restrict,
@@ -67,9 +97,11 @@ impl Kw {
         Kw::Signed,
         Kw::Sizeof,
         Kw::Static,
+        Kw::StaticAssert,
         Kw::Struct,
         Kw::Switch,
         Kw::Typedef,
+        Kw::Typeof,
         Kw::Union,
         Kw::Unsigned,
         Kw::Void,
@@ -79,8 +111,18 @@ impl Kw {
 
     pub fn to_str(self) -> &'static str {
         match self {
+            Kw::Alignas => "_Alignas",
+            Kw::Alignof => "_Alignof",
+            Kw::Asm => "__asm__",
+            Kw::Attribute => "__attribute__",
             Kw::Auto => "auto",
+            Kw::Bool => "_Bool",
             Kw::Break => "break",
+            Kw::BuiltinVaArg => "__builtin_va_arg",
+            Kw::BuiltinVaCopy => "__builtin_va_copy",
+            Kw::BuiltinVaEnd => "__builtin_va_end",
+            Kw::BuiltinVaList => "__builtin_va_list",
+            Kw::BuiltinVaStart => "__builtin_va_start",
             Kw::Case => "case",
             Kw::Char => "char",
             Kw::Const => "const",
@@ -90,13 +132,17 @@ impl Kw {
             Kw::Double => "double",
             Kw::Else => "else",
             Kw::Enum => "enum",
+            Kw::Extension => "__extension__",
             Kw::Extern => "extern",
             Kw::Float => "float",
             Kw::For => "for",
+            Kw::Generic => "_Generic",
+            Kw::GnuTypeof => "__typeof__",
             Kw::Goto => "goto",
             Kw::If => "if",
             Kw::Inline => "inline",
             Kw::Int => "int",
+            Kw::Int128 => "__int128",
             Kw::Long => "long",
             Kw::Register => "register",
             Kw::Restrict => "restrict",
@@ -105,9 +151,11 @@ impl Kw {
             Kw::Signed => "signed",
             Kw::Sizeof => "sizeof",
             Kw::Static => "static",
+            Kw::StaticAssert => "_Static_assert",
             Kw::Struct => "struct",
             Kw::Switch => "switch",
             Kw::Typedef => "typedef",
+            Kw::Typeof => "typeof",
             Kw::Union => "union",
             Kw::Unsigned => "unsigned",
             Kw::Void => "void",
@@ -118,7 +166,7 @@ impl Kw {
 }
 
 impl PrettyPrint for Kw {
-    fn pretty_fmt(&self, w: &mut impl fmt::Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         writeln!(w, "{}Kw {}", indent(depth), self.to_str())
     }
 }
@@ -170,6 +218,7 @@ pub enum Punct {
     Plus2,
     Dash2,
     Comma,
+    Ellipsis,
 }
 
 impl Punct {
@@ -219,6 +268,7 @@ impl Punct {
         Punct::Plus2,
         Punct::Dash2,
         Punct::Comma,
+        Punct::Ellipsis,
     ];
 
     pub fn to_str(self) -> &'static str {
@@ -268,12 +318,13 @@ impl Punct {
             Punct::Plus2 => "++",
             Punct::Dash2 => "--",
             Punct::Comma => ",",
+            Punct::Ellipsis => "...",
         }
     }
 }
 
 impl PrettyPrint for Punct {
-    fn pretty_fmt(&self, w: &mut impl fmt::Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         writeln!(w, "{}Punct {}", indent(depth), self.to_str())
     }
 }
@@ -287,7 +338,7 @@ pub enum Lit {
 }
 
 impl PrettyPrint for Lit {
-    fn pretty_fmt(&self, w: &mut impl fmt::Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         write!(w, "{}Lit ", indent(depth))?;
         match self {
             Lit::Int(val) => writeln!(w, "Int {}", val),
@@ -307,7 +358,7 @@ pub enum Token {
 }
 
 impl PrettyPrint for Token {
-    fn pretty_fmt(&self, w: &mut impl fmt::Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         write!(w, "{}Token ", indent(depth))?;
         match self {
             Token::Ident(ident) => write!(w, "Ident {:?}", ident),
@@ -320,3 +371,49 @@ impl PrettyPrint for Token {
         }
     }
 }
+
+impl fmt::Display for Token {
+    /// Writes the token as it would be spelled in source, e.g. `int`, `+=` or `"hi"`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(ident) => write!(f, "{ident}"),
+            Token::Lit(Lit::Int(val)) => write!(f, "{val}"),
+            Token::Lit(Lit::Char(val)) => write!(f, "{val:?}"),
+            Token::Lit(Lit::Float(val)) => write!(f, "{val}"),
+            Token::Lit(Lit::Str(val)) => write!(f, "{val:?}"),
+            Token::Kw(kw) => write!(f, "{}", kw.to_str()),
+            Token::Punct(punct) => write!(f, "{}", punct.to_str()),
+        }
+    }
+}
+
+/// A position in the source text, where lines and columns are counted from 1. The
+/// default position `0:0` stands for an unknown location.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Pos {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl fmt::Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.col)
+    }
+}
+
+/// The region of source text a token was lexed from, ending just past its last char
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Span {
+    pub start: Pos,
+    pub end: Pos,
+}
+
+impl Span {
+    /// Returns the smallest span covering both spans
+    pub fn join(self, other: Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+}
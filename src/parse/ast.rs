@@ -1,26 +1,54 @@
-use crate::{indent, Lit, PrettyPrint, Punct};
+use crate::{indent, Kw, Lit, PrettyPrint, PrettyWriter, Punct, Span};
 use std::fmt::{self, Write};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum BasicTy {
     Void,
+    Bool,
+    /// Plain `char`, which is distinct from both `signed char` and `unsigned char`
     Char,
+    SignedChar,
+    UnsignedChar,
+    Short,
+    UnsignedShort,
     Int,
     UnsignedInt,
+    Long,
+    UnsignedLong,
+    LongLong,
+    UnsignedLongLong,
+    Int128,
+    UnsignedInt128,
     Float,
     Double,
+    LongDouble,
+    /// The `__builtin_va_list` type behind `va_list`, whose layout depends on the ABI
+    VaList,
 }
 
 impl PrettyPrint for BasicTy {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         write!(w, "{}BasicTy ", indent(depth))?;
         match self {
             BasicTy::Void => write!(w, "Void")?,
+            BasicTy::Bool => write!(w, "Bool")?,
             BasicTy::Char => write!(w, "Char")?,
+            BasicTy::SignedChar => write!(w, "SignedChar")?,
+            BasicTy::UnsignedChar => write!(w, "UnsignedChar")?,
+            BasicTy::Short => write!(w, "Short")?,
+            BasicTy::UnsignedShort => write!(w, "UnsignedShort")?,
             BasicTy::Int => write!(w, "Int")?,
             BasicTy::UnsignedInt => write!(w, "UnsignedInt")?,
+            BasicTy::Long => write!(w, "Long")?,
+            BasicTy::UnsignedLong => write!(w, "UnsignedLong")?,
+            BasicTy::LongLong => write!(w, "LongLong")?,
+            BasicTy::UnsignedLongLong => write!(w, "UnsignedLongLong")?,
+            BasicTy::Int128 => write!(w, "Int128")?,
+            BasicTy::UnsignedInt128 => write!(w, "UnsignedInt128")?,
             BasicTy::Float => write!(w, "Float")?,
             BasicTy::Double => write!(w, "Double")?,
+            BasicTy::LongDouble => write!(w, "LongDouble")?,
+            BasicTy::VaList => write!(w, "VaList")?,
         }
         writeln!(w)
     }
@@ -32,54 +60,546 @@ pub struct PtrTy {
 }
 
 impl PrettyPrint for PtrTy {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         writeln!(w, "{}PtrTy ty", indent(depth))?;
         self.ty.pretty_fmt(w, depth + 1)
     }
 }
 
+/// A type qualifier, which may appear in the specifiers or after any `*`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TyQual {
+    Const,
+    Volatile,
+    Restrict,
+}
+
+impl TyQual {
+    pub fn from_kw(kw: Kw) -> Option<TyQual> {
+        match kw {
+            Kw::Const => Some(TyQual::Const),
+            Kw::Volatile => Some(TyQual::Volatile),
+            Kw::Restrict => Some(TyQual::Restrict),
+            _ => None,
+        }
+    }
+
+    pub fn to_kw(self) -> Kw {
+        match self {
+            TyQual::Const => Kw::Const,
+            TyQual::Volatile => Kw::Volatile,
+            TyQual::Restrict => Kw::Restrict,
+        }
+    }
+}
+
+impl PrettyPrint for TyQual {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}TyQual {}", indent(depth), self.to_kw().to_str())
+    }
+}
+
+/// A qualified type, e.g. the pointer in `char *const p`
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualTy {
+    pub quals: Vec<TyQual>,
+    pub ty: Box<Ty>,
+}
+
+impl PrettyPrint for QualTy {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}QualTy qual[{}] ty", indent(depth), self.quals.len())?;
+        for qual in &self.quals {
+            qual.pretty_fmt(w, depth + 1)?;
+        }
+        self.ty.pretty_fmt(w, depth + 1)
+    }
+}
+
+/// An attribute the compiler knows about. Attributes not in this table are warned
+/// about and dropped while parsing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttrKind {
+    Alias,
+    Aligned,
+    AlwaysInline,
+    Cleanup,
+    Cold,
+    Const,
+    Constructor,
+    Deprecated,
+    Destructor,
+    Fallthrough,
+    Format,
+    GnuInline,
+    Hot,
+    Malloc,
+    MaybeUnused,
+    Mode,
+    Nodiscard,
+    Noinline,
+    Nonnull,
+    Noreturn,
+    Nothrow,
+    Packed,
+    Pure,
+    Reproducible,
+    ReturnsNonnull,
+    Section,
+    Sentinel,
+    Unsequenced,
+    Unused,
+    Used,
+    Visibility,
+    WarnUnusedResult,
+    Weak,
+}
+
+impl AttrKind {
+    pub const ALL: &[AttrKind] = &[
+        AttrKind::Alias,
+        AttrKind::Aligned,
+        AttrKind::AlwaysInline,
+        AttrKind::Cleanup,
+        AttrKind::Cold,
+        AttrKind::Const,
+        AttrKind::Constructor,
+        AttrKind::Deprecated,
+        AttrKind::Destructor,
+        AttrKind::Fallthrough,
+        AttrKind::Format,
+        AttrKind::GnuInline,
+        AttrKind::Hot,
+        AttrKind::Malloc,
+        AttrKind::MaybeUnused,
+        AttrKind::Mode,
+        AttrKind::Nodiscard,
+        AttrKind::Noinline,
+        AttrKind::Nonnull,
+        AttrKind::Noreturn,
+        AttrKind::Nothrow,
+        AttrKind::Packed,
+        AttrKind::Pure,
+        AttrKind::Reproducible,
+        AttrKind::ReturnsNonnull,
+        AttrKind::Section,
+        AttrKind::Sentinel,
+        AttrKind::Unsequenced,
+        AttrKind::Unused,
+        AttrKind::Used,
+        AttrKind::Visibility,
+        AttrKind::WarnUnusedResult,
+        AttrKind::Weak,
+    ];
+
+    pub fn from_name(name: &str) -> Option<AttrKind> {
+        AttrKind::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.to_str() == name)
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            AttrKind::Alias => "alias",
+            AttrKind::Aligned => "aligned",
+            AttrKind::AlwaysInline => "always_inline",
+            AttrKind::Cleanup => "cleanup",
+            AttrKind::Cold => "cold",
+            AttrKind::Const => "const",
+            AttrKind::Constructor => "constructor",
+            AttrKind::Deprecated => "deprecated",
+            AttrKind::Destructor => "destructor",
+            AttrKind::Fallthrough => "fallthrough",
+            AttrKind::Format => "format",
+            AttrKind::GnuInline => "gnu_inline",
+            AttrKind::Hot => "hot",
+            AttrKind::Malloc => "malloc",
+            AttrKind::MaybeUnused => "maybe_unused",
+            AttrKind::Mode => "mode",
+            AttrKind::Nodiscard => "nodiscard",
+            AttrKind::Noinline => "noinline",
+            AttrKind::Nonnull => "nonnull",
+            AttrKind::Noreturn => "noreturn",
+            AttrKind::Nothrow => "nothrow",
+            AttrKind::Packed => "packed",
+            AttrKind::Pure => "pure",
+            AttrKind::Reproducible => "reproducible",
+            AttrKind::ReturnsNonnull => "returns_nonnull",
+            AttrKind::Section => "section",
+            AttrKind::Sentinel => "sentinel",
+            AttrKind::Unsequenced => "unsequenced",
+            AttrKind::Unused => "unused",
+            AttrKind::Used => "used",
+            AttrKind::Visibility => "visibility",
+            AttrKind::WarnUnusedResult => "warn_unused_result",
+            AttrKind::Weak => "weak",
+        }
+    }
+
+    /// Whether this is a standard attribute, which can be written `[[name]]` without a
+    /// vendor prefix
+    pub fn is_std(self) -> bool {
+        matches!(
+            self,
+            AttrKind::Deprecated
+                | AttrKind::Fallthrough
+                | AttrKind::MaybeUnused
+                | AttrKind::Nodiscard
+                | AttrKind::Noreturn
+                | AttrKind::Reproducible
+                | AttrKind::Unsequenced
+        )
+    }
+
+    /// Whether this is a GNU attribute, which can be written `__attribute__((name))` or
+    /// `[[gnu::name]]`
+    pub fn is_gnu(self) -> bool {
+        !matches!(
+            self,
+            AttrKind::MaybeUnused
+                | AttrKind::Nodiscard
+                | AttrKind::Reproducible
+                | AttrKind::Unsequenced
+        )
+    }
+}
+
+/// An attribute along with its arguments, from either `__attribute__((...))` or `[[...]]`
+/// `aligned(16)`, `format(printf, 1, 2)` or `deprecated("use g instead")`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attr {
+    pub kind: AttrKind,
+    pub args: Vec<Expr>,
+    pub span: Span,
+}
+
+impl PrettyPrint for Attr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}Attr", indent(depth))?;
+        w.span(self.span)?;
+        write!(w, " {}", self.kind.to_str())?;
+        if !self.args.is_empty() {
+            write!(w, " arg[{}]", self.args.len())?;
+        }
+        writeln!(w)?;
+        for arg in &self.args {
+            arg.pretty_fmt(w, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// A type with attributes, e.g. the pointer in `int *[[gnu::aligned(8)]] p`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttrTy {
+    pub attrs: Vec<Attr>,
+    pub ty: Box<Ty>,
+}
+
+impl PrettyPrint for AttrTy {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}AttrTy attr[{}] ty", indent(depth), self.attrs.len())?;
+        for attr in &self.attrs {
+            attr.pretty_fmt(w, depth + 1)?;
+        }
+        self.ty.pretty_fmt(w, depth + 1)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ArrayTy {
     pub ty: Box<Ty>,
-    pub length: u64,
+    /// The length, which is omitted for arrays of unknown size `int a[]`
+    pub length: Option<Box<Expr>>,
+    /// Qualifiers inside the brackets of a parameter, e.g. `int a[const]`, which
+    /// qualify the pointer the parameter is adjusted to
+    pub quals: Vec<TyQual>,
+    /// Whether a parameter promises at least `length` elements, e.g. `int a[static 10]`
+    pub is_static: bool,
+    /// Whether the length is `[*]`, a variable length array of unspecified size, which
+    /// is only allowed in parameters
+    pub is_star: bool,
 }
 
 impl PrettyPrint for ArrayTy {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
-        writeln!(w, "{}ArrayTy length ty", indent(depth),)?;
-        writeln!(w, "{}{}", indent(depth + 1), self.length)?;
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}ArrayTy", indent(depth))?;
+        if self.is_static {
+            write!(w, " static")?;
+        }
+        if self.is_star {
+            write!(w, " star")?;
+        }
+        if !self.quals.is_empty() {
+            write!(w, " qual[{}]", self.quals.len())?;
+        }
+        if self.length.is_some() {
+            write!(w, " length")?;
+        }
+        writeln!(w, " ty")?;
+        for qual in &self.quals {
+            qual.pretty_fmt(w, depth + 1)?;
+        }
+        if let Some(length) = &self.length {
+            length.pretty_fmt(w, depth + 1)?;
+        }
         self.ty.pretty_fmt(w, depth + 1)
     }
 }
 
+/// How the parameter list of a function declarator was written
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamStyle {
+    /// Parameters declared with their types, including `(void)` for no parameters
+    Prototype,
+    /// An empty list `()`, which leaves the parameters unspecified before C23 and
+    /// means no parameters from C23 on
+    Empty,
+    /// An old-style (K&R) identifier list `(a, b)`, whose types are declared between
+    /// the declarator and the body of the definition. Parameters without a
+    /// declaration are implicitly `int`.
+    OldStyle,
+}
+
+impl ParamStyle {
+    fn pretty_flag(self) -> &'static str {
+        match self {
+            ParamStyle::Prototype => "",
+            ParamStyle::Empty => " empty",
+            ParamStyle::OldStyle => " old_style",
+        }
+    }
+}
+
+/// A function type, as declared by a prototype or used through a function pointer
+/// `int (int, char *)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FnTy {
+    pub ret: Box<Ty>,
+    pub params: Vec<Param>,
+    /// Whether the parameters end with `...`
+    pub variadic: bool,
+    pub style: ParamStyle,
+}
+
+impl PrettyPrint for FnTy {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}FnTy ret param[{}]", indent(depth), self.params.len())?;
+        if self.variadic {
+            write!(w, " variadic")?;
+        }
+        writeln!(w, "{}", self.style.pretty_flag())?;
+        self.ret.pretty_fmt(w, depth + 1)?;
+        for param in &self.params {
+            param.pretty_fmt(w, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StructKind {
+    Struct,
+    Union,
+}
+
+impl PrettyPrint for StructKind {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}StructKind ", indent(depth))?;
+        match self {
+            StructKind::Struct => writeln!(w, "struct"),
+            StructKind::Union => writeln!(w, "union"),
+        }
+    }
+}
+
+/// A reference to a struct or union by its tag
+/// `struct my_struct`
 #[derive(Debug, Clone, PartialEq)]
 pub struct StructTy {
+    pub kind: StructKind,
     pub name: String,
 }
 
 impl PrettyPrint for StructTy {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
-        writeln!(w, "{}StructTy name", indent(depth))?;
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}StructTy kind name", indent(depth))?;
+        self.kind.pretty_fmt(w, depth + 1)?;
+        writeln!(w, "{}{:?}", indent(depth + 1), self.name)
+    }
+}
+
+/// A reference to an enum by its tag
+/// `enum color`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumTy {
+    pub name: String,
+}
+
+impl PrettyPrint for EnumTy {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}EnumTy name", indent(depth))?;
+        writeln!(w, "{}{:?}", indent(depth + 1), self.name)
+    }
+}
+
+/// A reference to a type by its typedef name
+/// `node_t`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedefTy {
+    pub name: String,
+}
+
+impl PrettyPrint for TypedefTy {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}TypedefTy name", indent(depth))?;
         writeln!(w, "{}{:?}", indent(depth + 1), self.name)
     }
 }
 
+/// The type of an expression or type name, a GNU extension
+/// `typeof(x)` or `__typeof__(int *)`
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeofTy {
+    Ty(Box<Ty>),
+    Expr(Box<Expr>),
+}
+
+impl PrettyPrint for TypeofTy {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}TypeofTy ", indent(depth))?;
+        match self {
+            TypeofTy::Ty(ty) => {
+                writeln!(w, "Ty ty")?;
+                ty.pretty_fmt(w, depth + 1)
+            }
+            TypeofTy::Expr(expr) => {
+                writeln!(w, "Expr expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+        }
+    }
+}
+
+/// An enumeration constant, with an optional explicit value
+/// `GREEN = 5`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enumerator {
+    pub attrs: Vec<Attr>,
+    pub ident: String,
+    pub value: Option<Expr>,
+}
+
+impl PrettyPrint for Enumerator {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}Enumerator", indent(depth))?;
+        if !self.attrs.is_empty() {
+            write!(w, " attr[{}]", self.attrs.len())?;
+        }
+        write!(w, " ident")?;
+        if self.value.is_some() {
+            write!(w, " value")?;
+        }
+        writeln!(w)?;
+        for attr in &self.attrs {
+            attr.pretty_fmt(w, depth + 1)?;
+        }
+        writeln!(w, "{}{:?}", indent(depth + 1), self.ident)?;
+        if let Some(value) = &self.value {
+            value.pretty_fmt(w, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// An enum definition, which may be anonymous and may have a C23 fixed underlying type
+/// `enum color : char { RED, GREEN = 5, BLUE }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumDefn {
+    pub attrs: Vec<Attr>,
+    pub name: Option<String>,
+    pub base: Option<Box<Ty>>,
+    pub variants: Vec<Enumerator>,
+}
+
+impl PrettyPrint for EnumDefn {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}EnumDefn", indent(depth))?;
+        if !self.attrs.is_empty() {
+            write!(w, " attr[{}]", self.attrs.len())?;
+        }
+        if self.name.is_some() {
+            write!(w, " name")?;
+        }
+        if self.base.is_some() {
+            write!(w, " base")?;
+        }
+        writeln!(w, " variant[{}]", self.variants.len())?;
+        for attr in &self.attrs {
+            attr.pretty_fmt(w, depth + 1)?;
+        }
+        if let Some(name) = &self.name {
+            writeln!(w, "{}{:?}", indent(depth + 1), name)?;
+        }
+        if let Some(base) = &self.base {
+            base.pretty_fmt(w, depth + 1)?;
+        }
+        for variant in &self.variants {
+            variant.pretty_fmt(w, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
-pub enum Ty {
+pub enum TyKind {
     Basic(BasicTy),
     Ptr(PtrTy),
     Array(ArrayTy),
     Struct(StructTy),
+    StructDefn(StructDefn),
+    Enum(EnumTy),
+    EnumDefn(EnumDefn),
+    Typedef(TypedefTy),
+    Typeof(TypeofTy),
+    Fn(FnTy),
+    Qual(QualTy),
+    Attr(AttrTy),
+}
+
+/// A type as written in the source. Types derived by a declarator, like pointers and
+/// arrays, span both their base type and the declarator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ty {
+    pub kind: TyKind,
+    pub span: Span,
+}
+
+impl Ty {
+    pub fn new(kind: TyKind, span: Span) -> Self {
+        Ty { kind, span }
+    }
 }
 
 impl PrettyPrint for Ty {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
-        writeln!(w, "{}Ty ty", indent(depth))?;
-        match self {
-            Ty::Basic(ty) => ty.pretty_fmt(w, depth + 1),
-            Ty::Ptr(ty) => ty.pretty_fmt(w, depth + 1),
-            Ty::Array(ty) => ty.pretty_fmt(w, depth + 1),
-            Ty::Struct(ty) => ty.pretty_fmt(w, depth + 1),
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}Ty", indent(depth))?;
+        w.span(self.span)?;
+        writeln!(w, " ty")?;
+        match &self.kind {
+            TyKind::Basic(ty) => ty.pretty_fmt(w, depth + 1),
+            TyKind::Ptr(ty) => ty.pretty_fmt(w, depth + 1),
+            TyKind::Array(ty) => ty.pretty_fmt(w, depth + 1),
+            TyKind::Struct(ty) => ty.pretty_fmt(w, depth + 1),
+            TyKind::StructDefn(ty) => ty.pretty_fmt(w, depth + 1),
+            TyKind::Enum(ty) => ty.pretty_fmt(w, depth + 1),
+            TyKind::EnumDefn(ty) => ty.pretty_fmt(w, depth + 1),
+            TyKind::Typedef(ty) => ty.pretty_fmt(w, depth + 1),
+            TyKind::Typeof(ty) => ty.pretty_fmt(w, depth + 1),
+            TyKind::Fn(ty) => ty.pretty_fmt(w, depth + 1),
+            TyKind::Qual(ty) => ty.pretty_fmt(w, depth + 1),
+            TyKind::Attr(ty) => ty.pretty_fmt(w, depth + 1),
         }
     }
 }
@@ -92,7 +612,7 @@ pub struct IdentExpr {
 }
 
 impl PrettyPrint for IdentExpr {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         writeln!(w, "{}IdentExpr {:?}", indent(depth), self.ident)
     }
 }
@@ -104,7 +624,7 @@ pub struct ConstExpr {
 }
 
 impl PrettyPrint for ConstExpr {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         writeln!(w, "{}ConstExpr lit", indent(depth))?;
         self.lit.pretty_fmt(w, depth + 1)
     }
@@ -112,26 +632,42 @@ impl PrettyPrint for ConstExpr {
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum UnOp {
-    Incr,
-    Decr,
+    PreIncr,
+    PreDecr,
+    PostIncr,
+    PostDecr,
     Pos,
     Neg,
+    Not,
+    BitNot,
 }
 
 impl UnOp {
     fn to_punct(&self) -> Punct {
         match self {
-            UnOp::Incr => Punct::Plus2,
-            UnOp::Decr => Punct::Dash2,
+            UnOp::PreIncr | UnOp::PostIncr => Punct::Plus2,
+            UnOp::PreDecr | UnOp::PostDecr => Punct::Dash2,
             UnOp::Pos => Punct::Plus,
             UnOp::Neg => Punct::Dash,
+            UnOp::Not => Punct::Exclam,
+            UnOp::BitNot => Punct::Tilde,
         }
     }
 }
 
 impl PrettyPrint for UnOp {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
-        writeln!(w, "{}UnOp {}", indent(depth), self.to_punct().to_str())
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        match self {
+            UnOp::PostIncr | UnOp::PostDecr => {
+                writeln!(
+                    w,
+                    "{}UnOp postfix {}",
+                    indent(depth),
+                    self.to_punct().to_str()
+                )
+            }
+            _ => writeln!(w, "{}UnOp {}", indent(depth), self.to_punct().to_str()),
+        }
     }
 }
 
@@ -142,7 +678,7 @@ pub struct UnOpExpr {
 }
 
 impl PrettyPrint for UnOpExpr {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         writeln!(w, "{}UnOpExpr op expr", indent(depth))?;
         self.op.pretty_fmt(w, depth + 1)?;
         self.expr.pretty_fmt(w, depth + 1)
@@ -155,29 +691,73 @@ pub enum BinOp {
     Sub,
     Mul,
     Div,
+    Mod,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+    LogAnd,
+    LogOr,
     Eq,
     Ne,
     Lt,
     Gt,
+    Le,
+    Ge,
 }
 
 impl BinOp {
+    pub fn from_punct(punct: Punct) -> Option<BinOp> {
+        match punct {
+            Punct::Plus => Some(BinOp::Add),
+            Punct::Dash => Some(BinOp::Sub),
+            Punct::Star => Some(BinOp::Mul),
+            Punct::Slash => Some(BinOp::Div),
+            Punct::Percent => Some(BinOp::Mod),
+            Punct::Amp => Some(BinOp::BitAnd),
+            Punct::Pipe => Some(BinOp::BitOr),
+            Punct::Hat => Some(BinOp::BitXor),
+            Punct::Lt2 => Some(BinOp::Shl),
+            Punct::Gt2 => Some(BinOp::Shr),
+            Punct::Amp2 => Some(BinOp::LogAnd),
+            Punct::Pipe2 => Some(BinOp::LogOr),
+            Punct::Eq2 => Some(BinOp::Eq),
+            Punct::ExclamEq => Some(BinOp::Ne),
+            Punct::Lt => Some(BinOp::Lt),
+            Punct::Gt => Some(BinOp::Gt),
+            Punct::LtEq => Some(BinOp::Le),
+            Punct::GtEq => Some(BinOp::Ge),
+            _ => None,
+        }
+    }
+
     fn to_punct(&self) -> Punct {
         match self {
             BinOp::Add => Punct::Plus,
             BinOp::Sub => Punct::Dash,
             BinOp::Mul => Punct::Star,
             BinOp::Div => Punct::Slash,
+            BinOp::Mod => Punct::Percent,
+            BinOp::BitAnd => Punct::Amp,
+            BinOp::BitOr => Punct::Pipe,
+            BinOp::BitXor => Punct::Hat,
+            BinOp::Shl => Punct::Lt2,
+            BinOp::Shr => Punct::Gt2,
+            BinOp::LogAnd => Punct::Amp2,
+            BinOp::LogOr => Punct::Pipe2,
             BinOp::Eq => Punct::Eq2,
             BinOp::Ne => Punct::ExclamEq,
-            BinOp::Lt => Punct::LtEq,
-            BinOp::Gt => Punct::GtEq,
+            BinOp::Lt => Punct::Lt,
+            BinOp::Gt => Punct::Gt,
+            BinOp::Le => Punct::LtEq,
+            BinOp::Ge => Punct::GtEq,
         }
     }
 }
 
 impl PrettyPrint for BinOp {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         writeln!(w, "{}BinOp {}", indent(depth), self.to_punct().to_str())
     }
 }
@@ -190,7 +770,7 @@ pub struct BinOpExpr {
 }
 
 impl PrettyPrint for BinOpExpr {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         writeln!(w, "{}BinOpExpr op left right", indent(depth))?;
         self.op.pretty_fmt(w, depth + 1)?;
         self.left.pretty_fmt(w, depth + 1)?;
@@ -198,15 +778,92 @@ impl PrettyPrint for BinOpExpr {
     }
 }
 
+/// An assignment operator, either plain `=` or a compound assignment like `+=`
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssnOp {
+    Assn,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+}
+
+impl AssnOp {
+    pub fn from_punct(punct: Punct) -> Option<AssnOp> {
+        match punct {
+            Punct::Eq => Some(AssnOp::Assn),
+            Punct::PlusEq => Some(AssnOp::Add),
+            Punct::DashEq => Some(AssnOp::Sub),
+            Punct::StarEq => Some(AssnOp::Mul),
+            Punct::SlashEq => Some(AssnOp::Div),
+            Punct::PercentEq => Some(AssnOp::Mod),
+            Punct::AmpEq => Some(AssnOp::BitAnd),
+            Punct::PipeEq => Some(AssnOp::BitOr),
+            Punct::HatEq => Some(AssnOp::BitXor),
+            Punct::Lt2Eq => Some(AssnOp::Shl),
+            Punct::Gt2Eq => Some(AssnOp::Shr),
+            _ => None,
+        }
+    }
+
+    fn to_punct(&self) -> Punct {
+        match self {
+            AssnOp::Assn => Punct::Eq,
+            AssnOp::Add => Punct::PlusEq,
+            AssnOp::Sub => Punct::DashEq,
+            AssnOp::Mul => Punct::StarEq,
+            AssnOp::Div => Punct::SlashEq,
+            AssnOp::Mod => Punct::PercentEq,
+            AssnOp::BitAnd => Punct::AmpEq,
+            AssnOp::BitOr => Punct::PipeEq,
+            AssnOp::BitXor => Punct::HatEq,
+            AssnOp::Shl => Punct::Lt2Eq,
+            AssnOp::Shr => Punct::Gt2Eq,
+        }
+    }
+
+    /// The binary operation performed by a compound assignment,
+    /// e.g. `a += b` behaves as `a = a + b`
+    pub fn to_bin_op(&self) -> Option<BinOp> {
+        match self {
+            AssnOp::Assn => None,
+            AssnOp::Add => Some(BinOp::Add),
+            AssnOp::Sub => Some(BinOp::Sub),
+            AssnOp::Mul => Some(BinOp::Mul),
+            AssnOp::Div => Some(BinOp::Div),
+            AssnOp::Mod => Some(BinOp::Mod),
+            AssnOp::BitAnd => Some(BinOp::BitAnd),
+            AssnOp::BitOr => Some(BinOp::BitOr),
+            AssnOp::BitXor => Some(BinOp::BitXor),
+            AssnOp::Shl => Some(BinOp::Shl),
+            AssnOp::Shr => Some(BinOp::Shr),
+        }
+    }
+}
+
+impl PrettyPrint for AssnOp {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}AssnOp {}", indent(depth), self.to_punct().to_str())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct AssnExpr {
     pub lvalue: Box<Expr>,
     pub expr: Box<Expr>,
+    pub op: AssnOp,
 }
 
 impl PrettyPrint for AssnExpr {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
-        writeln!(w, "{}AssnExpr lvalue expr", indent(depth))?;
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}AssnExpr op lvalue expr", indent(depth))?;
+        self.op.pretty_fmt(w, depth + 1)?;
         self.lvalue.pretty_fmt(w, depth + 1)?;
         self.expr.pretty_fmt(w, depth + 1)
     }
@@ -219,7 +876,7 @@ pub struct CallExpr {
 }
 
 impl PrettyPrint for CallExpr {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         writeln!(
             w,
             "{}CallExpr fun params[{}]",
@@ -241,20 +898,52 @@ pub struct MemberExpr {
 }
 
 impl PrettyPrint for MemberExpr {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         writeln!(w, "{}MemberExpr field expr", indent(depth))?;
         writeln!(w, "{}{:?}", indent(depth + 1), self.field)?;
         self.expr.pretty_fmt(w, depth + 1)
     }
 }
 
+/// A member access through a pointer
+/// `ptr->field`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrowExpr {
+    pub expr: Box<Expr>,
+    pub field: String,
+}
+
+impl PrettyPrint for ArrowExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}ArrowExpr field expr", indent(depth))?;
+        writeln!(w, "{}{:?}", indent(depth + 1), self.field)?;
+        self.expr.pretty_fmt(w, depth + 1)
+    }
+}
+
+/// An array subscript
+/// `arr[index]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexExpr {
+    pub expr: Box<Expr>,
+    pub index: Box<Expr>,
+}
+
+impl PrettyPrint for IndexExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}IndexExpr expr index", indent(depth))?;
+        self.expr.pretty_fmt(w, depth + 1)?;
+        self.index.pretty_fmt(w, depth + 1)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct DerefExpr {
     pub expr: Box<Expr>,
 }
 
 impl PrettyPrint for DerefExpr {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         writeln!(w, "{}DerefExpr expr", indent(depth))?;
         self.expr.pretty_fmt(w, depth + 1)
     }
@@ -266,7 +955,7 @@ pub struct RefExpr {
 }
 
 impl PrettyPrint for RefExpr {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         writeln!(w, "{}RefExpr expr", indent(depth))?;
         self.expr.pretty_fmt(w, depth + 1)
     }
@@ -278,7 +967,7 @@ pub struct ParenExpr {
 }
 
 impl PrettyPrint for ParenExpr {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         writeln!(w, "{}ParenExpr expr", indent(depth))?;
         self.expr.pretty_fmt(w, depth + 1)
     }
@@ -291,132 +980,853 @@ pub struct CommaExpr {
 }
 
 impl PrettyPrint for CommaExpr {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         writeln!(w, "{}CommaExpr left right", indent(depth))?;
         self.left.pretty_fmt(w, depth + 1)?;
         self.right.pretty_fmt(w, depth + 1)
     }
 }
 
+/// A conditional expression
+/// `cond ? expr_true : expr_false`
 #[derive(Debug, Clone, PartialEq)]
-pub enum Expr {
-    Ident(IdentExpr),
-    Const(ConstExpr),
-    Assn(AssnExpr),
-    BinOp(BinOpExpr),
-    UnOp(UnOpExpr),
-    Call(CallExpr),
-    Member(MemberExpr),
-    Deref(DerefExpr),
-    Ref(RefExpr),
-    Paren(ParenExpr),
-    Comma(CommaExpr),
+pub struct CondExpr {
+    pub cond: Box<Expr>,
+    pub expr_true: Box<Expr>,
+    pub expr_false: Box<Expr>,
 }
 
-impl PrettyPrint for Expr {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
-        write!(w, "{}Expr ", indent(depth))?;
-        match self {
-            Expr::Ident(expr) => {
-                write!(w, "Ident expr")?;
-                expr.pretty_fmt(w, depth + 1)
-            }
-            Expr::Const(expr) => {
-                write!(w, "Const expr")?;
-                expr.pretty_fmt(w, depth + 1)
-            }
-            Expr::Assn(expr) => {
-                write!(w, "Assn expr")?;
-                expr.pretty_fmt(w, depth + 1)
-            }
-            Expr::BinOp(expr) => {
-                write!(w, "BinOp expr")?;
-                expr.pretty_fmt(w, depth + 1)
-            }
-            Expr::UnOp(expr) => {
-                write!(w, "UnOp expr")?;
-                expr.pretty_fmt(w, depth + 1)
-            }
-            Expr::Call(expr) => {
-                write!(w, "Call expr")?;
-                expr.pretty_fmt(w, depth + 1)
-            }
-            Expr::Member(expr) => {
-                write!(w, "Member expr")?;
-                expr.pretty_fmt(w, depth + 1)
-            }
-            Expr::Deref(expr) => {
-                write!(w, "Deref expr")?;
-                expr.pretty_fmt(w, depth + 1)
-            }
-            Expr::Ref(expr) => {
-                write!(w, "Ref expr")?;
-                expr.pretty_fmt(w, depth + 1)
-            }
-            Expr::Paren(expr) => {
-                write!(w, "Paren expr")?;
-                expr.pretty_fmt(w, depth + 1)
-            }
-            Expr::Comma(expr) => {
-                write!(w, "Comma expr")?;
-                expr.pretty_fmt(w, depth + 1)
-            }
-        }
+impl PrettyPrint for CondExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}CondExpr cond expr_true expr_false", indent(depth))?;
+        self.cond.pretty_fmt(w, depth + 1)?;
+        self.expr_true.pretty_fmt(w, depth + 1)?;
+        self.expr_false.pretty_fmt(w, depth + 1)
     }
 }
 
+/// A cast expression
+/// `(unsigned int)x`
 #[derive(Debug, Clone, PartialEq)]
-pub struct DeclStmt {
+pub struct CastExpr {
     pub ty: Ty,
-    pub ident: String,
+    pub expr: Box<Expr>,
 }
 
-impl PrettyPrint for DeclStmt {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
-        writeln!(w, "{}DeclStmt ty ident", indent(depth))?;
+impl PrettyPrint for CastExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}CastExpr ty expr", indent(depth))?;
         self.ty.pretty_fmt(w, depth + 1)?;
-        writeln!(w, "{}{:?}", indent(depth + 1), self.ident)
+        self.expr.pretty_fmt(w, depth + 1)
     }
 }
 
+/// The size of an expression's type
+/// `sizeof x`
 #[derive(Debug, Clone, PartialEq)]
-pub struct BlockStmt {
-    pub stmts: Vec<Stmt>,
+pub struct SizeofExpr {
+    pub expr: Box<Expr>,
 }
 
-impl PrettyPrint for BlockStmt {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
-        writeln!(w, "{}BlockStmt stmts[{}]", indent(depth), self.stmts.len())?;
-        for stmt in &self.stmts {
-            stmt.pretty_fmt(w, depth + 1)?;
-        }
-        Ok(())
+impl PrettyPrint for SizeofExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}SizeofExpr expr", indent(depth))?;
+        self.expr.pretty_fmt(w, depth + 1)
     }
 }
 
+/// The size of a type
+/// `sizeof(int)`
 #[derive(Debug, Clone, PartialEq)]
-pub struct IfStmt {
-    pub cond: Expr,
-    pub stmt: Box<Stmt>,
+pub struct SizeofTyExpr {
+    pub ty: Ty,
 }
 
-impl PrettyPrint for IfStmt {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
-        writeln!(w, "{}IfStmt cond stmt", indent(depth))?;
-        self.cond.pretty_fmt(w, depth + 1)?;
-        self.stmt.pretty_fmt(w, depth + 1)
+impl PrettyPrint for SizeofTyExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}SizeofTyExpr ty", indent(depth))?;
+        self.ty.pretty_fmt(w, depth + 1)
     }
 }
 
+/// The alignment of a type
+/// `_Alignof(int)`
 #[derive(Debug, Clone, PartialEq)]
-pub struct IfElseStmt {
-    pub cond: Expr,
-    pub stmt_true: Box<Stmt>,
-    pub stmt_false: Box<Stmt>,
+pub struct AlignofExpr {
+    pub ty: Ty,
+}
+
+impl PrettyPrint for AlignofExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}AlignofExpr ty", indent(depth))?;
+        self.ty.pretty_fmt(w, depth + 1)
+    }
+}
+
+/// A generic selection, which evaluates to the association whose type matches the
+/// type of the controlling expression
+/// `_Generic(x, float: sqrtf, default: sqrt)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenericExpr {
+    pub ctrl: Box<Expr>,
+    pub assocs: Vec<GenericAssoc>,
+}
+
+impl PrettyPrint for GenericExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(
+            w,
+            "{}GenericExpr ctrl assoc[{}]",
+            indent(depth),
+            self.assocs.len()
+        )?;
+        self.ctrl.pretty_fmt(w, depth + 1)?;
+        for assoc in &self.assocs {
+            assoc.pretty_fmt(w, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// An association of a generic selection. The `default` association has no type.
+/// `float: sqrtf` or `default: sqrt`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenericAssoc {
+    pub ty: Option<Ty>,
+    pub expr: Expr,
+}
+
+impl PrettyPrint for GenericAssoc {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}GenericAssoc", indent(depth))?;
+        match &self.ty {
+            Some(_) => write!(w, " ty")?,
+            None => write!(w, " default")?,
+        }
+        writeln!(w, " expr")?;
+        if let Some(ty) = &self.ty {
+            ty.pretty_fmt(w, depth + 1)?;
+        }
+        self.expr.pretty_fmt(w, depth + 1)
+    }
+}
+
+/// Start processing the variable arguments following the parameter `last`
+/// `__builtin_va_start(ap, fmt)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct VaStartExpr {
+    pub ap: Box<Expr>,
+    pub last: Box<Expr>,
+}
+
+impl PrettyPrint for VaStartExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}VaStartExpr ap last", indent(depth))?;
+        self.ap.pretty_fmt(w, depth + 1)?;
+        self.last.pretty_fmt(w, depth + 1)
+    }
+}
+
+/// Fetch the next variable argument as the given type
+/// `__builtin_va_arg(ap, int)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct VaArgExpr {
+    pub ap: Box<Expr>,
+    pub ty: Ty,
+}
+
+impl PrettyPrint for VaArgExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}VaArgExpr ap ty", indent(depth))?;
+        self.ap.pretty_fmt(w, depth + 1)?;
+        self.ty.pretty_fmt(w, depth + 1)
+    }
+}
+
+/// Finish processing the variable arguments
+/// `__builtin_va_end(ap)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct VaEndExpr {
+    pub ap: Box<Expr>,
+}
+
+impl PrettyPrint for VaEndExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}VaEndExpr ap", indent(depth))?;
+        self.ap.pretty_fmt(w, depth + 1)
+    }
+}
+
+/// Copy the position in the variable arguments from `src` to `dest`
+/// `__builtin_va_copy(dest, src)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct VaCopyExpr {
+    pub dest: Box<Expr>,
+    pub src: Box<Expr>,
+}
+
+impl PrettyPrint for VaCopyExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}VaCopyExpr dest src", indent(depth))?;
+        self.dest.pretty_fmt(w, depth + 1)?;
+        self.src.pretty_fmt(w, depth + 1)
+    }
+}
+
+/// A block whose last statement gives the value of the expression, a GNU extension
+/// `({ int y = f(); y * y; })`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StmtExpr {
+    pub stmts: Vec<Stmt>,
+}
+
+impl PrettyPrint for StmtExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}StmtExpr stmts[{}]", indent(depth), self.stmts.len())?;
+        for stmt in &self.stmts {
+            stmt.pretty_fmt(w, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// The address of a label, for use with a computed goto, a GNU extension
+/// `&&retry`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelAddrExpr {
+    pub label: String,
+}
+
+impl PrettyPrint for LabelAddrExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}LabelAddrExpr label", indent(depth))?;
+        writeln!(w, "{}{:?}", indent(depth + 1), self.label)
+    }
+}
+
+/// An expression marked as deliberately using GNU extensions
+/// `__extension__ 0LL`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtensionExpr {
+    pub expr: Box<Expr>,
+}
+
+impl PrettyPrint for ExtensionExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}ExtensionExpr expr", indent(depth))?;
+        self.expr.pretty_fmt(w, depth + 1)
+    }
+}
+
+/// A call to a compiler builtin whose operands are all expressions, a GNU extension
+/// `__builtin_expect(n == 0, 0)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuiltinCallExpr {
+    pub name: String,
+    pub args: Vec<Expr>,
+}
+
+impl PrettyPrint for BuiltinCallExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(
+            w,
+            "{}BuiltinCallExpr name arg[{}]",
+            indent(depth),
+            self.args.len()
+        )?;
+        writeln!(w, "{}{:?}", indent(depth + 1), self.name)?;
+        for arg in &self.args {
+            arg.pretty_fmt(w, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// The byte offset of a member within a struct or union. The first designator is
+/// always a field, the rest may reach into nested members or array elements.
+/// `__builtin_offsetof(struct point, pos.x)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OffsetofExpr {
+    pub ty: Ty,
+    pub designators: Vec<Designator>,
+}
+
+impl PrettyPrint for OffsetofExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(
+            w,
+            "{}OffsetofExpr ty designator[{}]",
+            indent(depth),
+            self.designators.len()
+        )?;
+        self.ty.pretty_fmt(w, depth + 1)?;
+        for designator in &self.designators {
+            designator.pretty_fmt(w, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether two types are compatible, ignoring top-level qualifiers
+/// `__builtin_types_compatible_p(typeof(x), int)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypesCompatibleExpr {
+    pub lhs: Box<Ty>,
+    pub rhs: Box<Ty>,
+}
+
+impl PrettyPrint for TypesCompatibleExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}TypesCompatibleExpr lhs rhs", indent(depth))?;
+        self.lhs.pretty_fmt(w, depth + 1)?;
+        self.rhs.pretty_fmt(w, depth + 1)
+    }
+}
+
+/// A designator choosing which member or element of an aggregate to initialize
+/// `.field` or `[3]`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Designator {
+    Field(String),
+    Index(Expr),
+}
+
+impl PrettyPrint for Designator {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}Designator ", indent(depth))?;
+        match self {
+            Designator::Field(field) => {
+                writeln!(w, "Field field")?;
+                writeln!(w, "{}{:?}", indent(depth + 1), field)
+            }
+            Designator::Index(expr) => {
+                writeln!(w, "Index expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+        }
+    }
+}
+
+/// One entry of an initializer list, which may be designated. Several designators
+/// reach into nested aggregates, e.g. `.pos.x = 1`.
+/// `[2] = 7`
+#[derive(Debug, Clone, PartialEq)]
+pub struct InitItem {
+    pub designators: Vec<Designator>,
+    pub init: Initializer,
+}
+
+impl PrettyPrint for InitItem {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(
+            w,
+            "{}InitItem designator[{}] init",
+            indent(depth),
+            self.designators.len()
+        )?;
+        for designator in &self.designators {
+            designator.pretty_fmt(w, depth + 1)?;
+        }
+        self.init.pretty_fmt(w, depth + 1)
+    }
+}
+
+/// A brace-enclosed initializer list. Any members or elements not mentioned are
+/// initialized to zero.
+/// `{ 1, [3] = 7, .pos = { 2, 3 } }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct InitList {
+    pub items: Vec<InitItem>,
+}
+
+impl PrettyPrint for InitList {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}InitList item[{}]", indent(depth), self.items.len())?;
+        for item in &self.items {
+            item.pretty_fmt(w, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// The initial value of a declared object, either a single expression (which includes
+/// a string literal initializing a char array) or a brace-enclosed list
+#[derive(Debug, Clone, PartialEq)]
+pub enum Initializer {
+    Expr(Expr),
+    List(InitList),
+}
+
+impl PrettyPrint for Initializer {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}Initializer ", indent(depth))?;
+        match self {
+            Initializer::Expr(expr) => {
+                writeln!(w, "Expr expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            Initializer::List(list) => {
+                writeln!(w, "List list")?;
+                list.pretty_fmt(w, depth + 1)
+            }
+        }
+    }
+}
+
+/// An unnamed object of the given type, initialized by the list
+/// `(struct point){ .x = 1, .y = 2 }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompoundLitExpr {
+    pub ty: Ty,
+    pub init: InitList,
+}
+
+impl PrettyPrint for CompoundLitExpr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}CompoundLitExpr ty init", indent(depth))?;
+        self.ty.pretty_fmt(w, depth + 1)?;
+        self.init.pretty_fmt(w, depth + 1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    Ident(IdentExpr),
+    Const(ConstExpr),
+    Assn(AssnExpr),
+    BinOp(BinOpExpr),
+    UnOp(UnOpExpr),
+    Call(CallExpr),
+    Member(MemberExpr),
+    Arrow(ArrowExpr),
+    Index(IndexExpr),
+    Deref(DerefExpr),
+    Ref(RefExpr),
+    Paren(ParenExpr),
+    Comma(CommaExpr),
+    Cond(CondExpr),
+    Cast(CastExpr),
+    Sizeof(SizeofExpr),
+    SizeofTy(SizeofTyExpr),
+    Alignof(AlignofExpr),
+    CompoundLit(CompoundLitExpr),
+    Generic(GenericExpr),
+    VaStart(VaStartExpr),
+    VaArg(VaArgExpr),
+    VaEnd(VaEndExpr),
+    VaCopy(VaCopyExpr),
+    Stmt(StmtExpr),
+    LabelAddr(LabelAddrExpr),
+    Extension(ExtensionExpr),
+    BuiltinCall(BuiltinCallExpr),
+    Offsetof(OffsetofExpr),
+    TypesCompatible(TypesCompatibleExpr),
+}
+
+/// An expression, along with the source it was parsed from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Expr { kind, span }
+    }
+}
+
+impl PrettyPrint for Expr {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}Expr", indent(depth))?;
+        w.span(self.span)?;
+        write!(w, " ")?;
+        match &self.kind {
+            ExprKind::Ident(expr) => {
+                writeln!(w, "Ident expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Const(expr) => {
+                writeln!(w, "Const expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Assn(expr) => {
+                writeln!(w, "Assn expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::BinOp(expr) => {
+                writeln!(w, "BinOp expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::UnOp(expr) => {
+                writeln!(w, "UnOp expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Call(expr) => {
+                writeln!(w, "Call expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Member(expr) => {
+                writeln!(w, "Member expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Arrow(expr) => {
+                writeln!(w, "Arrow expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Index(expr) => {
+                writeln!(w, "Index expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Deref(expr) => {
+                writeln!(w, "Deref expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Ref(expr) => {
+                writeln!(w, "Ref expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Paren(expr) => {
+                writeln!(w, "Paren expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Comma(expr) => {
+                writeln!(w, "Comma expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Cond(expr) => {
+                writeln!(w, "Cond expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Cast(expr) => {
+                writeln!(w, "Cast expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Sizeof(expr) => {
+                writeln!(w, "Sizeof expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::SizeofTy(expr) => {
+                writeln!(w, "SizeofTy expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Alignof(expr) => {
+                writeln!(w, "Alignof expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::CompoundLit(expr) => {
+                writeln!(w, "CompoundLit expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Generic(expr) => {
+                writeln!(w, "Generic expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::VaStart(expr) => {
+                writeln!(w, "VaStart expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::VaArg(expr) => {
+                writeln!(w, "VaArg expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::VaEnd(expr) => {
+                writeln!(w, "VaEnd expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::VaCopy(expr) => {
+                writeln!(w, "VaCopy expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Stmt(expr) => {
+                writeln!(w, "Stmt expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::LabelAddr(expr) => {
+                writeln!(w, "LabelAddr expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Extension(expr) => {
+                writeln!(w, "Extension expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::BuiltinCall(expr) => {
+                writeln!(w, "BuiltinCall expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::Offsetof(expr) => {
+                writeln!(w, "Offsetof expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+            ExprKind::TypesCompatible(expr) => {
+                writeln!(w, "TypesCompatible expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+        }
+    }
+}
+
+/// A storage-class specifier. `typedef` is grammatically one of these, but declarations
+/// using it are represented by `TypedefDecl` instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StorageClass {
+    Typedef,
+    Extern,
+    Static,
+    Auto,
+    Register,
+}
+
+impl StorageClass {
+    pub fn from_kw(kw: Kw) -> Option<StorageClass> {
+        match kw {
+            Kw::Typedef => Some(StorageClass::Typedef),
+            Kw::Extern => Some(StorageClass::Extern),
+            Kw::Static => Some(StorageClass::Static),
+            Kw::Auto => Some(StorageClass::Auto),
+            Kw::Register => Some(StorageClass::Register),
+            _ => None,
+        }
+    }
+
+    pub fn to_kw(self) -> Kw {
+        match self {
+            StorageClass::Typedef => Kw::Typedef,
+            StorageClass::Extern => Kw::Extern,
+            StorageClass::Static => Kw::Static,
+            StorageClass::Auto => Kw::Auto,
+            StorageClass::Register => Kw::Register,
+        }
+    }
+}
+
+impl PrettyPrint for StorageClass {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}StorageClass {}", indent(depth), self.to_kw().to_str())
+    }
+}
+
+/// An alignment specifier, whose operand is either a type or a constant expression
+/// `_Alignas(double)` or `_Alignas(16)`
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlignSpec {
+    Ty(Ty),
+    Expr(Expr),
+}
+
+impl PrettyPrint for AlignSpec {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}AlignSpec ", indent(depth))?;
+        match self {
+            AlignSpec::Ty(ty) => {
+                writeln!(w, "Ty ty")?;
+                ty.pretty_fmt(w, depth + 1)
+            }
+            AlignSpec::Expr(expr) => {
+                writeln!(w, "Expr expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeclStmt {
+    pub attrs: Vec<Attr>,
+    pub storage: Option<StorageClass>,
+    /// Alignment specifiers, of which the strictest applies
+    pub align: Vec<AlignSpec>,
+    pub ty: Ty,
+    pub ident: String,
+    /// The symbol name given by a GNU `__asm__` label, e.g. `__asm__("real_name")`
+    pub asm_label: Option<String>,
+    pub init: Option<Initializer>,
+}
+
+impl PrettyPrint for DeclStmt {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}DeclStmt", indent(depth))?;
+        if !self.attrs.is_empty() {
+            write!(w, " attr[{}]", self.attrs.len())?;
+        }
+        if self.storage.is_some() {
+            write!(w, " storage")?;
+        }
+        if !self.align.is_empty() {
+            write!(w, " align[{}]", self.align.len())?;
+        }
+        write!(w, " ty ident")?;
+        if self.asm_label.is_some() {
+            write!(w, " asm_label")?;
+        }
+        if self.init.is_some() {
+            write!(w, " init")?;
+        }
+        writeln!(w)?;
+        for attr in &self.attrs {
+            attr.pretty_fmt(w, depth + 1)?;
+        }
+        if let Some(storage) = &self.storage {
+            storage.pretty_fmt(w, depth + 1)?;
+        }
+        for align in &self.align {
+            align.pretty_fmt(w, depth + 1)?;
+        }
+        self.ty.pretty_fmt(w, depth + 1)?;
+        writeln!(w, "{}{:?}", indent(depth + 1), self.ident)?;
+        if let Some(asm_label) = &self.asm_label {
+            writeln!(w, "{}{:?}", indent(depth + 1), asm_label)?;
+        }
+        if let Some(init) = &self.init {
+            init.pretty_fmt(w, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// A function declaration without a body, also known as a prototype
+/// `int printf(const char *fmt, ...);`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FnDecl {
+    pub attrs: Vec<Attr>,
+    pub storage: Option<StorageClass>,
+    pub is_inline: bool,
+    pub name: String,
+    /// The symbol name given by a GNU `__asm__` label, e.g. `__asm__("real_name")`
+    pub asm_label: Option<String>,
+    pub ty: FnTy,
+}
+
+impl PrettyPrint for FnDecl {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}FnDecl", indent(depth))?;
+        if !self.attrs.is_empty() {
+            write!(w, " attr[{}]", self.attrs.len())?;
+        }
+        if self.storage.is_some() {
+            write!(w, " storage")?;
+        }
+        if self.is_inline {
+            write!(w, " inline")?;
+        }
+        write!(w, " name")?;
+        if self.asm_label.is_some() {
+            write!(w, " asm_label")?;
+        }
+        writeln!(w, " ty")?;
+        for attr in &self.attrs {
+            attr.pretty_fmt(w, depth + 1)?;
+        }
+        if let Some(storage) = &self.storage {
+            storage.pretty_fmt(w, depth + 1)?;
+        }
+        writeln!(w, "{}{:?}", indent(depth + 1), self.name)?;
+        if let Some(asm_label) = &self.asm_label {
+            writeln!(w, "{}{:?}", indent(depth + 1), asm_label)?;
+        }
+        self.ty.pretty_fmt(w, depth + 1)
+    }
+}
+
+/// A declaration introducing a new name for a type
+/// `typedef struct node node_t;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedefDecl {
+    pub attrs: Vec<Attr>,
+    pub ty: Ty,
+    pub ident: String,
+}
+
+impl PrettyPrint for TypedefDecl {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}TypedefDecl", indent(depth))?;
+        if !self.attrs.is_empty() {
+            write!(w, " attr[{}]", self.attrs.len())?;
+        }
+        writeln!(w, " ty ident")?;
+        for attr in &self.attrs {
+            attr.pretty_fmt(w, depth + 1)?;
+        }
+        self.ty.pretty_fmt(w, depth + 1)?;
+        writeln!(w, "{}{:?}", indent(depth + 1), self.ident)
+    }
+}
+
+/// An assertion checked at compile time. The message is optional since C23.
+/// `_Static_assert(sizeof(long) == 8, "long must be 64 bits");`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaticAssert {
+    pub cond: Expr,
+    pub message: Option<String>,
+}
+
+impl PrettyPrint for StaticAssert {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}StaticAssert cond", indent(depth))?;
+        if self.message.is_some() {
+            write!(w, " message")?;
+        }
+        writeln!(w)?;
+        self.cond.pretty_fmt(w, depth + 1)?;
+        if let Some(message) = &self.message {
+            writeln!(w, "{}{:?}", indent(depth + 1), message)?;
+        }
+        Ok(())
+    }
+}
+
+/// An expression evaluated for its side effects
+/// `f(x);`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExprStmt {
+    pub expr: Expr,
+}
+
+impl PrettyPrint for ExprStmt {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}ExprStmt expr", indent(depth))?;
+        self.expr.pretty_fmt(w, depth + 1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockStmt {
+    pub stmts: Vec<Stmt>,
+}
+
+impl PrettyPrint for BlockStmt {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}BlockStmt stmts[{}]", indent(depth), self.stmts.len())?;
+        for stmt in &self.stmts {
+            stmt.pretty_fmt(w, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IfStmt {
+    pub cond: Expr,
+    pub stmt: Box<Stmt>,
+}
+
+impl PrettyPrint for IfStmt {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}IfStmt cond stmt", indent(depth))?;
+        self.cond.pretty_fmt(w, depth + 1)?;
+        self.stmt.pretty_fmt(w, depth + 1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IfElseStmt {
+    pub cond: Expr,
+    pub stmt_true: Box<Stmt>,
+    pub stmt_false: Box<Stmt>,
 }
 
 impl PrettyPrint for IfElseStmt {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         writeln!(w, "{}IfElseStmt cond stmt_true stmt_false", indent(depth))?;
         self.cond.pretty_fmt(w, depth + 1)?;
         self.stmt_true.pretty_fmt(w, depth + 1)?;
@@ -424,95 +1834,441 @@ impl PrettyPrint for IfElseStmt {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhileStmt {
+    pub cond: Expr,
+    pub stmt: Box<Stmt>,
+}
+
+impl PrettyPrint for WhileStmt {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}WhileStmt cond stmt", indent(depth))?;
+        self.cond.pretty_fmt(w, depth + 1)?;
+        self.stmt.pretty_fmt(w, depth + 1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DoWhileStmt {
+    pub stmt: Box<Stmt>,
+    pub cond: Expr,
+}
+
+impl PrettyPrint for DoWhileStmt {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}DoWhileStmt stmt cond", indent(depth))?;
+        self.stmt.pretty_fmt(w, depth + 1)?;
+        self.cond.pretty_fmt(w, depth + 1)
+    }
+}
+
+/// The first clause of a for loop, which may declare variables scoped to the loop
+#[derive(Debug, Clone, PartialEq)]
+pub enum ForInit {
+    Decl(Vec<DeclStmt>),
+    Expr(Expr),
+}
+
+impl PrettyPrint for ForInit {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}ForInit ", indent(depth))?;
+        match self {
+            ForInit::Decl(stmts) => {
+                writeln!(w, "Decl stmt[{}]", stmts.len())?;
+                for stmt in stmts {
+                    stmt.pretty_fmt(w, depth + 1)?;
+                }
+                Ok(())
+            }
+            ForInit::Expr(expr) => {
+                writeln!(w, "Expr expr")?;
+                expr.pretty_fmt(w, depth + 1)
+            }
+        }
+    }
+}
+
+/// A for loop, where any of the clauses may be omitted
+/// `for (init; cond; step) stmt`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForStmt {
+    pub init: Option<ForInit>,
+    pub cond: Option<Expr>,
+    pub step: Option<Expr>,
+    pub stmt: Box<Stmt>,
+}
+
+impl PrettyPrint for ForStmt {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}ForStmt", indent(depth))?;
+        if self.init.is_some() {
+            write!(w, " init")?;
+        }
+        if self.cond.is_some() {
+            write!(w, " cond")?;
+        }
+        if self.step.is_some() {
+            write!(w, " step")?;
+        }
+        writeln!(w, " stmt")?;
+        if let Some(init) = &self.init {
+            init.pretty_fmt(w, depth + 1)?;
+        }
+        if let Some(cond) = &self.cond {
+            cond.pretty_fmt(w, depth + 1)?;
+        }
+        if let Some(step) = &self.step {
+            step.pretty_fmt(w, depth + 1)?;
+        }
+        self.stmt.pretty_fmt(w, depth + 1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchStmt {
+    pub cond: Expr,
+    pub stmt: Box<Stmt>,
+}
+
+impl PrettyPrint for SwitchStmt {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}SwitchStmt cond stmt", indent(depth))?;
+        self.cond.pretty_fmt(w, depth + 1)?;
+        self.stmt.pretty_fmt(w, depth + 1)
+    }
+}
+
+/// A statement labeled with a case of the enclosing switch. The label is a constant
+/// expression, or with GNU extensions an inclusive range of them.
+/// `case expr: stmt` or `case expr ... expr_end: stmt`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaseStmt {
+    pub expr: Expr,
+    pub expr_end: Option<Expr>,
+    pub stmt: Box<Stmt>,
+}
+
+impl PrettyPrint for CaseStmt {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        match &self.expr_end {
+            Some(expr_end) => {
+                writeln!(w, "{}CaseStmt expr expr_end stmt", indent(depth))?;
+                self.expr.pretty_fmt(w, depth + 1)?;
+                expr_end.pretty_fmt(w, depth + 1)?;
+            }
+            None => {
+                writeln!(w, "{}CaseStmt expr stmt", indent(depth))?;
+                self.expr.pretty_fmt(w, depth + 1)?;
+            }
+        }
+        self.stmt.pretty_fmt(w, depth + 1)
+    }
+}
+
+/// A statement labeled as the default case of the enclosing switch
+/// `default: stmt`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefaultStmt {
+    pub stmt: Box<Stmt>,
+}
+
+impl PrettyPrint for DefaultStmt {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}DefaultStmt stmt", indent(depth))?;
+        self.stmt.pretty_fmt(w, depth + 1)
+    }
+}
+
+/// A statement labeled as the target of a goto
+/// `label: stmt`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelStmt {
+    pub label: String,
+    pub stmt: Box<Stmt>,
+}
+
+impl PrettyPrint for LabelStmt {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}LabelStmt label stmt", indent(depth))?;
+        writeln!(w, "{}{:?}", indent(depth + 1), self.label)?;
+        self.stmt.pretty_fmt(w, depth + 1)
+    }
+}
+
+/// `goto label;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GotoStmt {
+    pub label: String,
+}
+
+impl PrettyPrint for GotoStmt {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}GotoStmt label", indent(depth))?;
+        writeln!(w, "{}{:?}", indent(depth + 1), self.label)
+    }
+}
+
+/// A jump to the label whose address `expr` evaluates to, a GNU extension
+/// `goto *targets[i];`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputedGotoStmt {
+    pub expr: Expr,
+}
+
+impl PrettyPrint for ComputedGotoStmt {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        writeln!(w, "{}ComputedGotoStmt expr", indent(depth))?;
+        self.expr.pretty_fmt(w, depth + 1)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ReturnStmt {
     pub expr: Option<Expr>,
 }
 
 impl PrettyPrint for ReturnStmt {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         write!(w, "{}ReturnStmt", indent(depth))?;
         match &self.expr {
             Some(expr) => {
                 writeln!(w, " expr")?;
                 expr.pretty_fmt(w, depth + 1)
             }
-            None => Ok(()),
+            None => writeln!(w),
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum Stmt {
+pub enum StmtKind {
     /// The empty statement `;`
     Empty,
     Decl(DeclStmt),
+    FnDecl(FnDecl),
+    StructDefn(StructDefn),
+    /// A forward declaration of a struct or union `struct my_struct;`
+    StructDecl(StructTy),
+    EnumDefn(EnumDefn),
+    Typedef(TypedefDecl),
+    StaticAssert(StaticAssert),
+    Expr(ExprStmt),
     Block(BlockStmt),
     If(IfStmt),
     IfElse(IfElseStmt),
+    While(WhileStmt),
+    DoWhile(DoWhileStmt),
+    For(ForStmt),
+    Switch(SwitchStmt),
+    Case(CaseStmt),
+    Default(DefaultStmt),
+    Label(LabelStmt),
+    Goto(GotoStmt),
+    ComputedGoto(ComputedGotoStmt),
+    /// The statement `break;`
+    Break,
+    /// The statement `continue;`
+    Continue,
     Return(ReturnStmt),
 }
 
+/// A statement, along with the source it was parsed from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub span: Span,
+    /// Attributes on the statement, e.g. `[[fallthrough]];`. Attributes on a
+    /// declaration belong to what it declares instead.
+    pub attrs: Vec<Attr>,
+}
+
+impl Stmt {
+    pub fn new(kind: StmtKind, span: Span) -> Self {
+        Stmt {
+            kind,
+            span,
+            attrs: Vec::new(),
+        }
+    }
+}
+
 impl PrettyPrint for Stmt {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
-        write!(w, "{}Stmt ", indent(depth))?;
-        match self {
-            Stmt::Empty => writeln!(w, "Empty"),
-            Stmt::Decl(stmt) => {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}Stmt", indent(depth))?;
+        w.span(self.span)?;
+        if !self.attrs.is_empty() {
+            write!(w, " attr[{}]", self.attrs.len())?;
+        }
+        write!(w, " ")?;
+        match &self.kind {
+            StmtKind::Empty => writeln!(w, "Empty"),
+            StmtKind::Decl(stmt) => {
                 writeln!(w, "Decl stmt")?;
                 stmt.pretty_fmt(w, depth + 1)
             }
-            Stmt::Block(stmt) => {
+            StmtKind::FnDecl(fn_decl) => {
+                writeln!(w, "FnDecl fn_decl")?;
+                fn_decl.pretty_fmt(w, depth + 1)
+            }
+            StmtKind::StructDefn(struct_defn) => {
+                writeln!(w, "StructDefn struct_defn")?;
+                struct_defn.pretty_fmt(w, depth + 1)
+            }
+            StmtKind::StructDecl(struct_ty) => {
+                writeln!(w, "StructDecl struct_ty")?;
+                struct_ty.pretty_fmt(w, depth + 1)
+            }
+            StmtKind::EnumDefn(enum_defn) => {
+                writeln!(w, "EnumDefn enum_defn")?;
+                enum_defn.pretty_fmt(w, depth + 1)
+            }
+            StmtKind::Typedef(typedef) => {
+                writeln!(w, "Typedef typedef")?;
+                typedef.pretty_fmt(w, depth + 1)
+            }
+            StmtKind::StaticAssert(static_assert) => {
+                writeln!(w, "StaticAssert static_assert")?;
+                static_assert.pretty_fmt(w, depth + 1)
+            }
+            StmtKind::Expr(stmt) => {
+                writeln!(w, "Expr stmt")?;
+                stmt.pretty_fmt(w, depth + 1)
+            }
+            StmtKind::Block(stmt) => {
                 writeln!(w, "Block stmt")?;
                 stmt.pretty_fmt(w, depth + 1)
             }
-            Stmt::If(stmt) => {
+            StmtKind::If(stmt) => {
                 writeln!(w, "If stmt")?;
                 stmt.pretty_fmt(w, depth + 1)
             }
-            Stmt::IfElse(stmt) => {
+            StmtKind::IfElse(stmt) => {
                 writeln!(w, "IfElse stmt")?;
                 stmt.pretty_fmt(w, depth + 1)
             }
-            Stmt::Return(stmt) => {
+            StmtKind::While(stmt) => {
+                writeln!(w, "While stmt")?;
+                stmt.pretty_fmt(w, depth + 1)
+            }
+            StmtKind::DoWhile(stmt) => {
+                writeln!(w, "DoWhile stmt")?;
+                stmt.pretty_fmt(w, depth + 1)
+            }
+            StmtKind::For(stmt) => {
+                writeln!(w, "For stmt")?;
+                stmt.pretty_fmt(w, depth + 1)
+            }
+            StmtKind::Switch(stmt) => {
+                writeln!(w, "Switch stmt")?;
+                stmt.pretty_fmt(w, depth + 1)
+            }
+            StmtKind::Case(stmt) => {
+                writeln!(w, "Case stmt")?;
+                stmt.pretty_fmt(w, depth + 1)
+            }
+            StmtKind::Default(stmt) => {
+                writeln!(w, "Default stmt")?;
+                stmt.pretty_fmt(w, depth + 1)
+            }
+            StmtKind::Label(stmt) => {
+                writeln!(w, "Label stmt")?;
+                stmt.pretty_fmt(w, depth + 1)
+            }
+            StmtKind::Goto(stmt) => {
+                writeln!(w, "Goto stmt")?;
+                stmt.pretty_fmt(w, depth + 1)
+            }
+            StmtKind::ComputedGoto(stmt) => {
+                writeln!(w, "ComputedGoto stmt")?;
+                stmt.pretty_fmt(w, depth + 1)
+            }
+            StmtKind::Break => writeln!(w, "Break"),
+            StmtKind::Continue => writeln!(w, "Continue"),
+            StmtKind::Return(stmt) => {
                 writeln!(w, "Return stmt")?;
                 stmt.pretty_fmt(w, depth + 1)
             }
+        }?;
+        for attr in &self.attrs {
+            attr.pretty_fmt(w, depth + 1)?;
         }
+        Ok(())
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Param {
-    pub ident: String,
+    pub attrs: Vec<Attr>,
+    /// The name, which may be omitted outside of function definitions `int (int, char *)`
+    pub ident: Option<String>,
     pub ty: Ty,
+    pub span: Span,
 }
 
 impl PrettyPrint for Param {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
-        writeln!(w, "{}Param ident ty", indent(depth))?;
-        writeln!(w, "{}{:?}", indent(depth + 1), self.ident)?;
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}Param", indent(depth))?;
+        w.span(self.span)?;
+        if !self.attrs.is_empty() {
+            write!(w, " attr[{}]", self.attrs.len())?;
+        }
+        if self.ident.is_some() {
+            write!(w, " ident")?;
+        }
+        writeln!(w, " ty")?;
+        for attr in &self.attrs {
+            attr.pretty_fmt(w, depth + 1)?;
+        }
+        if let Some(ident) = &self.ident {
+            writeln!(w, "{}{:?}", indent(depth + 1), ident)?;
+        }
         self.ty.pretty_fmt(w, depth + 1)
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct FnDefn {
+    pub attrs: Vec<Attr>,
+    /// Either `extern` or `static`, which determines the linkage
+    pub storage: Option<StorageClass>,
+    pub is_inline: bool,
     pub name: String,
     pub ret: Ty,
     pub params: Vec<Param>,
+    /// Whether the parameters end with `...`
+    pub variadic: bool,
+    pub style: ParamStyle,
     pub stmts: Vec<Stmt>,
 }
 
 impl PrettyPrint for FnDefn {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
-        writeln!(
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}FnDefn", indent(depth))?;
+        if !self.attrs.is_empty() {
+            write!(w, " attr[{}]", self.attrs.len())?;
+        }
+        if self.storage.is_some() {
+            write!(w, " storage")?;
+        }
+        if self.is_inline {
+            write!(w, " inline")?;
+        }
+        write!(
             w,
-            "{}FnDefn name ret param[{}] stmt[{}]",
-            indent(depth),
+            " name ret param[{}] stmt[{}]",
             self.params.len(),
             self.stmts.len()
         )?;
+        if self.variadic {
+            write!(w, " variadic")?;
+        }
+        writeln!(w, "{}", self.style.pretty_flag())?;
+        for attr in &self.attrs {
+            attr.pretty_fmt(w, depth + 1)?;
+        }
+        if let Some(storage) = &self.storage {
+            storage.pretty_fmt(w, depth + 1)?;
+        }
         writeln!(w, "{}{:?}", indent(depth + 1), self.name)?;
         self.ret.pretty_fmt(w, depth + 1)?;
         for param in &self.params {
@@ -525,60 +2281,233 @@ impl PrettyPrint for FnDefn {
     }
 }
 
+/// A member of a struct or union. Anonymous struct and union members have no
+/// identifier, and bit-fields have a width.
+/// `unsigned int flags : 3;`
 #[derive(Debug, Clone, PartialEq)]
 pub struct StructField {
+    pub attrs: Vec<Attr>,
+    /// Alignment specifiers, of which the strictest applies
+    pub align: Vec<AlignSpec>,
     pub ty: Ty,
-    pub ident: String,
+    pub ident: Option<String>,
+    pub bits: Option<Expr>,
 }
 
 impl PrettyPrint for StructField {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
-        writeln!(w, "{}StructField ty ident", indent(depth))?;
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}StructField", indent(depth))?;
+        if !self.attrs.is_empty() {
+            write!(w, " attr[{}]", self.attrs.len())?;
+        }
+        if !self.align.is_empty() {
+            write!(w, " align[{}]", self.align.len())?;
+        }
+        write!(w, " ty")?;
+        if self.ident.is_some() {
+            write!(w, " ident")?;
+        }
+        if self.bits.is_some() {
+            write!(w, " bits")?;
+        }
+        writeln!(w)?;
+        for attr in &self.attrs {
+            attr.pretty_fmt(w, depth + 1)?;
+        }
+        for align in &self.align {
+            align.pretty_fmt(w, depth + 1)?;
+        }
         self.ty.pretty_fmt(w, depth + 1)?;
-        writeln!(w, "{}{:?}", indent(depth + 1), self.ident)
+        if let Some(ident) = &self.ident {
+            writeln!(w, "{}{:?}", indent(depth + 1), ident)?;
+        }
+        if let Some(bits) = &self.bits {
+            bits.pretty_fmt(w, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// A member declaration of a struct or union, which is either a field or a static assertion
+/// `int x : 3;` or `_Static_assert(sizeof(int) == 4, "int");`
+#[derive(Debug, Clone, PartialEq)]
+pub enum StructMember {
+    Field(StructField),
+    StaticAssert(StaticAssert),
+}
+
+impl PrettyPrint for StructMember {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}StructMember ", indent(depth))?;
+        match self {
+            StructMember::Field(field) => {
+                writeln!(w, "Field field")?;
+                field.pretty_fmt(w, depth + 1)
+            }
+            StructMember::StaticAssert(static_assert) => {
+                writeln!(w, "StaticAssert static_assert")?;
+                static_assert.pretty_fmt(w, depth + 1)
+            }
+        }
     }
 }
 
+/// A struct or union definition, which may be anonymous when used as a type
+/// `struct my_struct { int a; char b; }`
 #[derive(Debug, Clone, PartialEq)]
 pub struct StructDefn {
-    pub name: String,
-    pub fields: Vec<StructField>,
+    pub attrs: Vec<Attr>,
+    pub kind: StructKind,
+    pub name: Option<String>,
+    pub members: Vec<StructMember>,
 }
 
 impl PrettyPrint for StructDefn {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
-        writeln!(
-            w,
-            "{}StructDefn name field[{}]",
-            indent(depth),
-            self.fields.len()
-        )?;
-        writeln!(w, "{}{:?}", indent(depth + 1), self.name)?;
-        for field in &self.fields {
-            field.pretty_fmt(w, depth + 1)?;
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}StructDefn", indent(depth))?;
+        if !self.attrs.is_empty() {
+            write!(w, " attr[{}]", self.attrs.len())?;
+        }
+        write!(w, " kind")?;
+        if self.name.is_some() {
+            write!(w, " name")?;
+        }
+        writeln!(w, " member[{}]", self.members.len())?;
+        for attr in &self.attrs {
+            attr.pretty_fmt(w, depth + 1)?;
+        }
+        self.kind.pretty_fmt(w, depth + 1)?;
+        if let Some(name) = &self.name {
+            writeln!(w, "{}{:?}", indent(depth + 1), name)?;
+        }
+        for member in &self.members {
+            member.pretty_fmt(w, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// A variable declared outside of any function
+/// `extern int errno;` or `static int count = 0;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlobalVar {
+    pub attrs: Vec<Attr>,
+    /// Either `extern` or `static`, which determines the linkage
+    pub storage: Option<StorageClass>,
+    /// Alignment specifiers, of which the strictest applies
+    pub align: Vec<AlignSpec>,
+    pub ty: Ty,
+    pub ident: String,
+    /// The symbol name given by a GNU `__asm__` label, e.g. `__asm__("real_name")`
+    pub asm_label: Option<String>,
+    pub init: Option<Initializer>,
+}
+
+impl PrettyPrint for GlobalVar {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}GlobalVar", indent(depth))?;
+        if !self.attrs.is_empty() {
+            write!(w, " attr[{}]", self.attrs.len())?;
+        }
+        if self.storage.is_some() {
+            write!(w, " storage")?;
+        }
+        if !self.align.is_empty() {
+            write!(w, " align[{}]", self.align.len())?;
+        }
+        write!(w, " ty ident")?;
+        if self.asm_label.is_some() {
+            write!(w, " asm_label")?;
+        }
+        if self.init.is_some() {
+            write!(w, " init")?;
+        }
+        writeln!(w)?;
+        for attr in &self.attrs {
+            attr.pretty_fmt(w, depth + 1)?;
+        }
+        if let Some(storage) = &self.storage {
+            storage.pretty_fmt(w, depth + 1)?;
+        }
+        for align in &self.align {
+            align.pretty_fmt(w, depth + 1)?;
+        }
+        self.ty.pretty_fmt(w, depth + 1)?;
+        writeln!(w, "{}{:?}", indent(depth + 1), self.ident)?;
+        if let Some(asm_label) = &self.asm_label {
+            writeln!(w, "{}{:?}", indent(depth + 1), asm_label)?;
+        }
+        if let Some(init) = &self.init {
+            init.pretty_fmt(w, depth + 1)?;
         }
         Ok(())
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum Item {
+pub enum ItemKind {
     FnDefn(FnDefn),
+    FnDecl(FnDecl),
+    GlobalVar(GlobalVar),
     StructDefn(StructDefn),
+    /// A forward declaration of a struct or union `struct my_struct;`
+    StructDecl(StructTy),
+    EnumDefn(EnumDefn),
+    Typedef(TypedefDecl),
+    StaticAssert(StaticAssert),
+}
+
+/// A top-level item, along with the source it was parsed from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    pub kind: ItemKind,
+    pub span: Span,
+}
+
+impl Item {
+    pub fn new(kind: ItemKind, span: Span) -> Self {
+        Item { kind, span }
+    }
 }
 
 impl PrettyPrint for Item {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
-        write!(w, "{}Item ", indent(depth))?;
-        match self {
-            Item::FnDefn(fn_defn) => {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
+        write!(w, "{}Item", indent(depth))?;
+        w.span(self.span)?;
+        write!(w, " ")?;
+        match &self.kind {
+            ItemKind::FnDefn(fn_defn) => {
                 writeln!(w, "FnDefn fn_defn")?;
                 fn_defn.pretty_fmt(w, depth + 1)
             }
-            Item::StructDefn(struct_defn) => {
+            ItemKind::FnDecl(fn_decl) => {
+                writeln!(w, "FnDecl fn_decl")?;
+                fn_decl.pretty_fmt(w, depth + 1)
+            }
+            ItemKind::GlobalVar(global_var) => {
+                writeln!(w, "GlobalVar global_var")?;
+                global_var.pretty_fmt(w, depth + 1)
+            }
+            ItemKind::StructDefn(struct_defn) => {
                 writeln!(w, "StructDefn struct_defn")?;
                 struct_defn.pretty_fmt(w, depth + 1)
             }
+            ItemKind::StructDecl(struct_ty) => {
+                writeln!(w, "StructDecl struct_ty")?;
+                struct_ty.pretty_fmt(w, depth + 1)
+            }
+            ItemKind::EnumDefn(enum_defn) => {
+                writeln!(w, "EnumDefn enum_defn")?;
+                enum_defn.pretty_fmt(w, depth + 1)
+            }
+            ItemKind::Typedef(typedef) => {
+                writeln!(w, "Typedef typedef")?;
+                typedef.pretty_fmt(w, depth + 1)
+            }
+            ItemKind::StaticAssert(static_assert) => {
+                writeln!(w, "StaticAssert static_assert")?;
+                static_assert.pretty_fmt(w, depth + 1)
+            }
         }
     }
 }
@@ -589,7 +2518,7 @@ pub struct Program {
 }
 
 impl PrettyPrint for Program {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result {
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result {
         writeln!(w, "{}Program item[{}]", indent(depth), self.items.len())?;
         for item in &self.items {
             item.pretty_fmt(w, depth + 1)?;
@@ -1,4 +1,5 @@
 mod ast;
+#[allow(clippy::module_inception)]
 mod parse;
 
 pub use ast::*;
@@ -1,20 +1,93 @@
 use crate::{
-    ArrayTy, BasicTy, FnDefn, Item, Kw, Lit, Param, Program, PtrTy, Punct, Stmt, StructTy, Token,
-    Ty,
+    AlignSpec, AlignofExpr, ArrayTy, ArrowExpr, AssnExpr, AssnOp, Attr, AttrKind, AttrTy, BasicTy,
+    BinOp, BinOpExpr, BlockStmt, BuiltinCallExpr, CallExpr, CaseStmt, CastExpr, CommaExpr,
+    CompoundLitExpr, ComputedGotoStmt, CondExpr, ConstExpr, DeclStmt, DefaultStmt, DerefExpr,
+    Designator, DoWhileStmt, EnumDefn, EnumTy, Enumerator, Expr, ExprKind, ExprStmt, ExtensionExpr,
+    FnDecl, FnDefn, FnTy, ForInit, ForStmt, GenericAssoc, GenericExpr, GlobalVar, GotoStmt,
+    IdentExpr, IfElseStmt, IfStmt, IndexExpr, InitItem, InitList, Initializer, Item, ItemKind, Kw,
+    LabelAddrExpr, LabelStmt, Lit, MemberExpr, OffsetofExpr, Param, ParamStyle, ParenExpr, Pos,
+    Program, PtrTy, Punct, QualTy, RefExpr, ReturnStmt, SizeofExpr, SizeofTyExpr, Span,
+    StaticAssert, Stmt, StmtExpr, StmtKind, StorageClass, StructDefn, StructField, StructKind,
+    StructMember, StructTy, SwitchStmt, Token, Ty, TyKind, TyQual, TypedefDecl, TypedefTy,
+    TypeofTy, TypesCompatibleExpr, UnOp, UnOpExpr, VaArgExpr, VaCopyExpr, VaEndExpr, VaStartExpr,
+    WhileStmt,
 };
-use anyhow::{bail, Context, Result};
+use anyhow::{bail, Result};
+use std::{collections::HashMap, fmt, rc::Rc};
+
+/// Options controlling which dialect of C the parser accepts
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Accept GNU extensions, e.g. case ranges `case 1 ... 5:`, statement expressions
+    /// `({ ... })` or `typeof(x)`. Otherwise they are rejected with an error, except that
+    /// `typeof` is an ordinary identifier.
+    pub gnu_extensions: bool,
+}
+
+/// An error encountered while parsing, located at the token where parsing failed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// Where the error occurred, if the tokens came with spans
+    pub pos: Option<Pos>,
+    pub message: String,
+    /// Index of the offending token, used to tell which of two errors came later
+    idx: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.pos {
+            Some(pos) => write!(f, "{pos}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The result of parsing a program with error recovery. When `errors` is not empty,
+/// `program` holds whatever items could be parsed around them.
+#[derive(Debug, Clone)]
+pub struct ParseOutput {
+    pub program: Program,
+    pub errors: Vec<ParseError>,
+    /// Problems that did not stop the program from being parsed, like unknown attributes
+    pub warnings: Vec<ParseError>,
+}
 
 /// Allows a parser to consume a stream of tokens
 #[derive(Clone)]
 struct TokenCursor<'a> {
     idx: usize,
     tokens: &'a [Token],
+    /// The span of each token, or empty if the tokens were lexed without spans
+    spans: &'a [Span],
+    options: ParseOptions,
+    /// Errors that have been recovered from so far
+    errors: Rc<Vec<ParseError>>,
+    warnings: Rc<Vec<ParseError>>,
+    /// Ordinary identifiers declared in each enclosing scope (innermost last), mapped to
+    /// whether they name a typedef. Shared between hypothetical cursors until modified.
+    scopes: Rc<Vec<HashMap<String, bool>>>,
 }
 
 impl<'a> TokenCursor<'a> {
     /// Create a new token cursor
     pub fn new(tokens: &'a [Token]) -> Self {
-        TokenCursor { idx: 0, tokens }
+        TokenCursor::with_options(tokens, &[], ParseOptions::default())
+    }
+
+    /// Create a new token cursor that parses with the given options
+    pub fn with_options(tokens: &'a [Token], spans: &'a [Span], options: ParseOptions) -> Self {
+        TokenCursor {
+            idx: 0,
+            tokens,
+            spans,
+            options,
+            errors: Rc::new(Vec::new()),
+            warnings: Rc::new(Vec::new()),
+            scopes: Rc::new(vec![HashMap::new()]),
+        }
     }
 
     /// Returns the first unconsumed token
@@ -27,270 +100,2967 @@ impl<'a> TokenCursor<'a> {
         &self.tokens[self.idx..]
     }
 
-    /// Returns whether there are any remaining unconsumed tokens
-    pub fn is_empty(&self) -> bool {
-        self.peek().is_none()
+    /// Returns whether there are any remaining unconsumed tokens
+    pub fn is_empty(&self) -> bool {
+        self.peek().is_none()
+    }
+
+    /// Advance the cursor by 1. Will panic if advanced past the end of the stream, so
+    /// you must ensure that there are tokens to consume. This function is usually paired
+    /// with `TokenCursor::peek` or `TokenCursor::npeek`.
+    pub fn advance(&mut self, amount: usize) {
+        if self.idx + amount > self.tokens.len() {
+            panic!("advanced past end of cursor");
+        }
+        self.idx += amount;
+    }
+
+    /// Replace the current cursor with another cursor, usually used to commit a "hypothetical" cursor
+    pub fn replace(&mut self, cursor: TokenCursor<'a>) {
+        let _ = std::mem::replace(self, cursor);
+    }
+
+    /// Returns the source position of the token at `idx`, or the end of the last token
+    /// if `idx` is past the end of the stream
+    pub fn pos_at(&self, idx: usize) -> Option<Pos> {
+        match self.spans.get(idx) {
+            Some(span) => Some(span.start),
+            None => self.spans.last().map(|span| span.end),
+        }
+    }
+
+    /// Returns the span of the tokens from index `start` up to the cursor
+    pub fn span_from(&self, start: usize) -> Span {
+        let start_pos = self.pos_at(start).unwrap_or_default();
+        let end_pos = match self.spans.get(self.idx.wrapping_sub(1)) {
+            Some(span) if self.idx > start => span.end,
+            _ => start_pos,
+        };
+        Span {
+            start: start_pos,
+            end: end_pos,
+        }
+    }
+
+    /// Returns the span of the most recently consumed token
+    pub fn prev_span(&self) -> Span {
+        self.span_from(self.idx.saturating_sub(1))
+    }
+
+    /// Record a warning located at the token at `idx`. Hypothetical cursors may parse
+    /// the same tokens more than once, so only the first warning at a token is kept.
+    pub fn warn_at(&mut self, idx: usize, message: impl Into<String>) {
+        if self.warnings.iter().all(|warning| warning.idx != idx) {
+            let warning = self.error_at(idx, message);
+            Rc::make_mut(&mut self.warnings).push(warning);
+        }
+    }
+
+    /// Create an error located at the token at `idx`
+    pub fn error_at(&self, idx: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            pos: self.pos_at(idx),
+            message: message.into(),
+            idx,
+        }
+    }
+
+    /// Create an error located at the first unconsumed token
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        self.error_at(self.idx, message)
+    }
+
+    /// Create an error saying what was expected in place of the first unconsumed token
+    pub fn expected(&self, expected: impl fmt::Display) -> ParseError {
+        let found = match self.peek() {
+            Some(token) => format!("`{token}`"),
+            None => String::from("end of file"),
+        };
+        self.error(format!("expected {expected}, found {found}"))
+    }
+
+    /// Fail with `message` at the first unconsumed token unless GNU extensions are enabled
+    pub fn require_gnu(&self, message: &str) -> Result<()> {
+        if !self.options.gnu_extensions {
+            bail!(self.error(message));
+        }
+        Ok(())
+    }
+
+    /// Record an error without skipping anything. Errors at or before one that was
+    /// already reported are fallout from it, so they are left out.
+    pub fn report(&mut self, err: ParseError) {
+        if self.errors.last().is_none_or(|last| last.idx < err.idx) {
+            Rc::make_mut(&mut self.errors).push(err);
+        }
+    }
+
+    /// Record an error, then skip the rest of the item that began at index `start`. The
+    /// item ends just after a `;` or function-like `) { }` body following the error, or
+    /// before the `}` closing the enclosing block. A `;` also ends an initializer list the
+    /// error left open. A keyword that begins the next declaration also ends the item,
+    /// unless it is nested in the item or continues the specifiers of the failed one.
+    pub fn recover(&mut self, err: anyhow::Error, start: usize) {
+        let err = match err.downcast::<ParseError>() {
+            Ok(err) => err,
+            Err(err) => self.error(err.to_string()),
+        };
+        let err_idx = self.idx.max(err.idx);
+        self.report(err);
+        let at_file_scope = self.scopes.len() == 1;
+        // A `;` inside parentheses only belongs to the item in a for loop header
+        let is_for = matches!(self.tokens.get(start), Some(Token::Kw(Kw::For)));
+        // Whether the outermost braces follow a `)`, or the `;` ending the parameter
+        // declarations of an old-style definition, so end a function or statement body
+        // rather than a struct or initializer that the declaration continues after
+        let mut is_body = false;
+        // Whether each open brace begins an initializer list, which cannot contain a `;`
+        let mut braces: Vec<bool> = Vec::new();
+        let mut parens: usize = 0;
+        self.idx = start;
+        while let Some(token) = self.peek() {
+            // Tokens before the error only tell us how deeply nested it was
+            let after_err = self.idx >= err_idx;
+            let depth = braces.len();
+            let prev = self.idx.checked_sub(1).map(|idx| &self.tokens[idx]);
+            match token {
+                Token::Punct(Punct::LBrace) => {
+                    if depth == 0 {
+                        is_body = match prev {
+                            Some(Token::Punct(Punct::RParen)) => true,
+                            Some(Token::Punct(Punct::Semi)) => at_file_scope,
+                            _ => false,
+                        };
+                    }
+                    let is_init = match prev {
+                        Some(Token::Punct(Punct::Eq)) => true,
+                        Some(Token::Punct(Punct::Comma | Punct::LBrace)) => {
+                            braces.last() == Some(&true)
+                        }
+                        _ => false,
+                    };
+                    braces.push(is_init);
+                }
+                Token::Punct(Punct::RBrace) if depth == 0 && !at_file_scope => return,
+                Token::Punct(Punct::RBrace) if depth == 1 && is_body && after_err => {
+                    self.advance(1);
+                    return;
+                }
+                Token::Punct(Punct::RBrace) => {
+                    braces.pop();
+                }
+                // Brackets can hold keywords too, e.g. `[static 3]`
+                Token::Punct(Punct::LParen | Punct::LBrack) => parens += 1,
+                Token::Punct(Punct::RParen | Punct::RBrack) => parens = parens.saturating_sub(1),
+                Token::Punct(Punct::Semi)
+                    if depth == 0 && (parens == 0 || !is_for) && after_err =>
+                {
+                    self.advance(1);
+                    // Old-style parameter declarations are followed by the body
+                    let is_param_decl =
+                        at_file_scope && matches!(self.peek(), Some(Token::Punct(Punct::LBrace)));
+                    if !is_param_decl {
+                        return;
+                    }
+                    continue;
+                }
+                // An initializer list left open by the error ends with the declaration
+                Token::Punct(Punct::Semi) if braces.last() == Some(&true) && after_err => {
+                    self.advance(1);
+                    return;
+                }
+                // Not after another keyword or a `*`, which the keyword would continue the
+                // specifiers or pointer qualifiers of, except the `__extension__` marker
+                Token::Kw(kw)
+                    if depth == 0
+                        && parens == 0
+                        && after_err
+                        && self.idx > start
+                        && !matches!(prev, Some(Token::Punct(Punct::Star)))
+                        && !matches!(prev, Some(Token::Kw(prev)) if *prev != Kw::Extension)
+                        && (matches!(kw, Kw::StaticAssert | Kw::Attribute | Kw::Extension)
+                            || is_decl_spec_start(self)) =>
+                {
+                    return
+                }
+                _ => {}
+            }
+            self.advance(1);
+        }
+    }
+
+    /// Enter a new block scope
+    pub fn push_scope(&mut self) {
+        Rc::make_mut(&mut self.scopes).push(HashMap::new());
+    }
+
+    /// Leave the innermost block scope, forgetting everything declared in it
+    pub fn pop_scope(&mut self) {
+        Rc::make_mut(&mut self.scopes).pop();
+    }
+
+    /// Declare an identifier in the innermost scope, shadowing any outer declaration
+    pub fn declare(&mut self, ident: &str, is_typedef: bool) {
+        let scopes = Rc::make_mut(&mut self.scopes);
+        let scope = scopes.last_mut().expect("no scope to declare in");
+        scope.insert(ident.to_string(), is_typedef);
+    }
+
+    /// Returns whether an identifier currently refers to a typedef name
+    pub fn is_typedef(&self, ident: &str) -> bool {
+        for scope in self.scopes.iter().rev() {
+            if let Some(&is_typedef) = scope.get(ident) {
+                return is_typedef;
+            }
+        }
+        false
+    }
+}
+
+/// The specifiers at the start of a declaration, e.g. `static const unsigned long`
+struct DeclSpecs {
+    storage: Option<StorageClass>,
+    is_inline: bool,
+    align: Vec<AlignSpec>,
+    /// Attributes among the specifiers, which apply to everything declared
+    attrs: Vec<Attr>,
+    ty: Ty,
+}
+
+/// Returns whether the next token begins a declaration
+fn is_decl_spec_start(cursor: &TokenCursor<'_>) -> bool {
+    match cursor.peek() {
+        Some(&Token::Kw(kw)) => {
+            is_basic_ty_kw(kw)
+                || TyQual::from_kw(kw).is_some()
+                || StorageClass::from_kw(kw).is_some()
+                || matches!(
+                    kw,
+                    Kw::Struct
+                        | Kw::Union
+                        | Kw::Enum
+                        | Kw::Inline
+                        | Kw::Alignas
+                        | Kw::Typeof
+                        | Kw::GnuTypeof
+                )
+        }
+        Some(Token::Ident(name)) => cursor.is_typedef(name),
+        _ => false,
+    }
+}
+
+/// Returns whether the next tokens begin a declaration, possibly after some attributes
+/// or `__extension__`
+fn is_decl_start(cursor: &TokenCursor<'_>) -> bool {
+    let mut new_cursor = cursor.clone();
+    loop {
+        match new_cursor.peek() {
+            Some(Token::Kw(Kw::Extension)) => new_cursor.advance(1),
+            _ if is_attr_start(&new_cursor) => {
+                if munch_attrs(&mut new_cursor).is_err() {
+                    return false;
+                }
+            }
+            _ => return is_decl_spec_start(&new_cursor),
+        }
+    }
+}
+
+/// Parse the attributes and `__extension__` markers that may come before a declaration
+/// e.g. `__extension__ [[maybe_unused]]`
+fn munch_decl_prefix(cursor: &mut TokenCursor<'_>) -> Result<Vec<Attr>> {
+    let mut attrs = Vec::new();
+    loop {
+        match cursor.peek() {
+            Some(Token::Kw(Kw::Extension)) => {
+                cursor.require_gnu("`__extension__` is a GNU extension")?;
+                cursor.advance(1);
+            }
+            _ if is_attr_start(cursor) => attrs.append(&mut munch_attrs(cursor)?),
+            _ => return Ok(attrs),
+        }
+    }
+}
+
+/// Parse the specifiers at the start of a declaration, which may be written in any order
+/// e.g. `static const unsigned int`, `long const unsigned` or `extern inline node_t`
+fn munch_decl_specs(cursor: &mut TokenCursor<'_>) -> Result<DeclSpecs> {
+    let start = cursor.idx;
+    let mut storage = None;
+    let mut is_inline = false;
+    let mut align = Vec::new();
+    let mut attrs = Vec::new();
+    let mut quals = Vec::new();
+    let mut kws = Vec::new();
+    let mut ty = None;
+    loop {
+        match *cursor.npeek() {
+            [Token::Kw(kw), ..] if TyQual::from_kw(kw).is_some() => {
+                quals.extend(TyQual::from_kw(kw));
+                cursor.advance(1);
+            }
+            [Token::Kw(kw), ..] if StorageClass::from_kw(kw).is_some() => {
+                if let Some(prev) = storage.map(StorageClass::to_kw) {
+                    bail!(cursor.error(format!(
+                        "cannot combine `{}` with `{}`",
+                        prev.to_str(),
+                        kw.to_str()
+                    )));
+                }
+                storage = StorageClass::from_kw(kw);
+                cursor.advance(1);
+            }
+            [Token::Kw(Kw::Inline), ..] => {
+                is_inline = true;
+                cursor.advance(1);
+            }
+            [Token::Kw(Kw::Alignas), ..] => {
+                cursor.advance(1);
+                align.push(munch_align_spec(cursor)?);
+            }
+            // Only marks the declaration as using extensions, so there is nothing to keep
+            [Token::Kw(Kw::Extension), ..] => {
+                cursor.require_gnu("`__extension__` is a GNU extension")?;
+                cursor.advance(1);
+            }
+            [Token::Kw(Kw::Attribute), ..]
+            | [Token::Punct(Punct::LBrack), Token::Punct(Punct::LBrack), ..] => {
+                attrs.append(&mut munch_attrs(cursor)?);
+            }
+            [Token::Kw(kw), ..] if is_basic_ty_kw(kw) => {
+                if ty.is_some() {
+                    bail!(cursor.error(format!(
+                        "cannot combine `{}` with previous type",
+                        kw.to_str()
+                    )));
+                }
+                kws.push(kw);
+                cursor.advance(1);
+            }
+            [Token::Kw(kw @ (Kw::Struct | Kw::Union | Kw::Enum)), ..] => {
+                if ty.is_some() || !kws.is_empty() {
+                    bail!(cursor.error(format!(
+                        "cannot combine `{}` with previous type",
+                        kw.to_str()
+                    )));
+                }
+                ty = Some(match kw {
+                    Kw::Enum => munch_enum_spec(cursor)?,
+                    _ => munch_struct_spec(cursor)?,
+                });
+            }
+            [Token::Kw(kw @ (Kw::Typeof | Kw::GnuTypeof)), ..] => {
+                cursor.require_gnu(&format!("`{}` is a GNU extension", kw.to_str()))?;
+                if ty.is_some() || !kws.is_empty() {
+                    bail!(cursor.error(format!(
+                        "cannot combine `{}` with previous type",
+                        kw.to_str()
+                    )));
+                }
+                let ty_start = cursor.idx;
+                cursor.advance(1);
+                let kind = TyKind::Typeof(munch_typeof(cursor)?);
+                ty = Some(Ty::new(kind, cursor.span_from(ty_start)));
+            }
+            // Once a type has been seen, an identifier must be the declarator instead,
+            // e.g. `unsigned T;` declares a variable named `T`
+            [Token::Ident(ref name), ..]
+                if ty.is_none() && kws.is_empty() && cursor.is_typedef(name) =>
+            {
+                let kind = TyKind::Typedef(TypedefTy { name: name.clone() });
+                cursor.advance(1);
+                ty = Some(Ty::new(kind, cursor.prev_span()));
+            }
+            _ => break,
+        }
+    }
+    let span = cursor.span_from(start);
+    let ty = match ty {
+        Some(ty) => ty,
+        None => Ty::new(TyKind::Basic(basic_ty_from_kws(cursor, &kws)?), span),
+    };
+    Ok(DeclSpecs {
+        storage,
+        is_inline,
+        align,
+        attrs,
+        ty: qualify_ty(cursor, ty, quals, span)?,
+    })
+}
+
+/// Returns whether the next tokens begin an attribute specifier
+fn is_attr_start(cursor: &TokenCursor<'_>) -> bool {
+    matches!(
+        cursor.npeek(),
+        [Token::Kw(Kw::Attribute), ..]
+            | [Token::Punct(Punct::LBrack), Token::Punct(Punct::LBrack), ..]
+    )
+}
+
+/// Parse any number of GNU `__attribute__((...))` or C23 `[[...]]` attribute specifiers.
+/// Unknown attributes are warned about and left out.
+/// e.g. `__attribute__((noreturn, format(printf, 1, 2)))` or `[[nodiscard]] [[gnu::cold]]`
+fn munch_attrs(cursor: &mut TokenCursor<'_>) -> Result<Vec<Attr>> {
+    let mut attrs = Vec::new();
+    loop {
+        match cursor.npeek() {
+            [Token::Kw(Kw::Attribute), ..] => {
+                cursor.advance(1);
+                munch_punct(cursor, Punct::LParen)?;
+                munch_punct(cursor, Punct::LParen)?;
+                attrs.append(&mut munch_attr_list(cursor, true, Punct::RParen)?);
+                munch_punct(cursor, Punct::RParen)?;
+                munch_punct(cursor, Punct::RParen)?;
+            }
+            [Token::Punct(Punct::LBrack), Token::Punct(Punct::LBrack), ..] => {
+                cursor.advance(2);
+                attrs.append(&mut munch_attr_list(cursor, false, Punct::RBrack)?);
+                munch_punct(cursor, Punct::RBrack)?;
+                munch_punct(cursor, Punct::RBrack)?;
+            }
+            _ => break,
+        }
+    }
+    Ok(attrs)
+}
+
+/// Parse the comma separated attributes inside an attribute specifier, up to the
+/// closing `)` or `]`. Empty entries are allowed, e.g. `(( , packed, ))`.
+fn munch_attr_list(cursor: &mut TokenCursor<'_>, is_gnu: bool, close: Punct) -> Result<Vec<Attr>> {
+    let mut attrs = Vec::new();
+    loop {
+        match cursor.peek() {
+            Some(&Token::Punct(punct)) if punct == close => break,
+            Some(Token::Punct(Punct::Comma)) => cursor.advance(1),
+            _ => {
+                attrs.extend(munch_attr(cursor, is_gnu)?);
+                match cursor.peek() {
+                    Some(Token::Punct(Punct::Comma)) => {}
+                    Some(&Token::Punct(punct)) if punct == close => {}
+                    _ => bail!(cursor.expected(format!("`,` or `{}`", close.to_str()))),
+                }
+            }
+        }
+    }
+    Ok(attrs)
+}
+
+/// Parse a single attribute and its arguments, returning nothing if it is unknown
+/// e.g. `packed`, `__aligned__(16)`, `gnu::cold` or `deprecated("use g instead")`
+fn munch_attr(cursor: &mut TokenCursor<'_>, is_gnu: bool) -> Result<Option<Attr>> {
+    let start = cursor.idx;
+    // Standard attributes may have a vendor prefix, e.g. `gnu::`
+    let mut prefix = None;
+    if let [Token::Ident(vendor), Token::Punct(Punct::Colon), Token::Punct(Punct::Colon), ..] =
+        cursor.npeek()
+    {
+        if !is_gnu {
+            prefix = Some(vendor.clone());
+            cursor.advance(3);
+        }
+    }
+    let name = match cursor.peek() {
+        Some(Token::Ident(name)) => name.clone(),
+        // Keywords can name attributes too, e.g. `__attribute__((const))`
+        Some(Token::Kw(kw)) => kw.to_str().to_string(),
+        _ => bail!(cursor.expected("attribute")),
+    };
+    cursor.advance(1);
+    // `__name__` is the same attribute as `name`, spelled so a macro can't replace it
+    let bare = name
+        .strip_prefix("__")
+        .and_then(|name| name.strip_suffix("__"))
+        .unwrap_or(&name);
+    let kind = match prefix.as_deref() {
+        None if is_gnu => AttrKind::from_name(bare).filter(|kind| kind.is_gnu()),
+        None => AttrKind::from_name(bare).filter(|kind| kind.is_std()),
+        Some("gnu" | "__gnu__") => AttrKind::from_name(bare).filter(|kind| kind.is_gnu()),
+        Some(_) => None,
+    };
+    let Some(kind) = kind else {
+        if let Some(Token::Punct(Punct::LParen)) = cursor.peek() {
+            skip_parens(cursor)?;
+        }
+        let spelling = match prefix {
+            Some(prefix) => format!("{prefix}::{name}"),
+            None => name,
+        };
+        cursor.warn_at(start, format!("unknown attribute `{spelling}` ignored"));
+        return Ok(None);
+    };
+    let mut args = Vec::new();
+    if let Some(Token::Punct(Punct::LParen)) = cursor.peek() {
+        cursor.advance(1);
+        if let Some(Token::Punct(Punct::RParen)) = cursor.peek() {
+            cursor.advance(1);
+        } else {
+            loop {
+                args.push(munch_assn_expr(cursor)?);
+                match cursor.peek() {
+                    Some(Token::Punct(Punct::Comma)) => cursor.advance(1),
+                    Some(Token::Punct(Punct::RParen)) => {
+                        cursor.advance(1);
+                        break;
+                    }
+                    _ => bail!(cursor.expected("`,` or `)`")),
+                }
+            }
+        }
+    }
+    Ok(Some(Attr {
+        kind,
+        args,
+        span: cursor.span_from(start),
+    }))
+}
+
+/// Skip over a parenthesized sequence of tokens, including any nested parentheses
+fn skip_parens(cursor: &mut TokenCursor<'_>) -> Result<()> {
+    let mut depth: usize = 0;
+    loop {
+        match cursor.peek() {
+            Some(Token::Punct(Punct::LParen)) => depth += 1,
+            Some(Token::Punct(Punct::RParen)) => {
+                depth -= 1;
+                if depth == 0 {
+                    cursor.advance(1);
+                    return Ok(());
+                }
+            }
+            Some(_) => {}
+            None => bail!(cursor.expected("`)`")),
+        }
+        cursor.advance(1);
+    }
+}
+
+/// Parse the operand of an alignment specifier after `_Alignas`
+/// e.g. `(double)` or `(16)`
+fn munch_align_spec(cursor: &mut TokenCursor<'_>) -> Result<AlignSpec> {
+    if let Some(ty) = munch_paren_ty_name(cursor)? {
+        return Ok(AlignSpec::Ty(ty));
+    }
+    munch_punct(cursor, Punct::LParen)?;
+    let expr = munch_cond_expr(cursor)?;
+    munch_punct(cursor, Punct::RParen)?;
+    Ok(AlignSpec::Expr(expr))
+}
+
+/// Parse the operand of `typeof` or `__typeof__`
+/// e.g. `(x + 1)` or `(struct node *)`
+fn munch_typeof(cursor: &mut TokenCursor<'_>) -> Result<TypeofTy> {
+    if let Some(ty) = munch_paren_ty_name(cursor)? {
+        return Ok(TypeofTy::Ty(Box::new(ty)));
+    }
+    munch_punct(cursor, Punct::LParen)?;
+    let expr = munch_expr(cursor)?;
+    munch_punct(cursor, Punct::RParen)?;
+    Ok(TypeofTy::Expr(Box::new(expr)))
+}
+
+/// Wrap a type with the given qualifiers, ignoring any repeats like `const const int`.
+/// The qualified type covers `span`, which includes the qualifiers.
+fn qualify_ty(cursor: &TokenCursor<'_>, ty: Ty, quals: Vec<TyQual>, span: Span) -> Result<Ty> {
+    if quals.is_empty() {
+        return Ok(ty);
+    }
+    // Only pointers may be restrict qualified, which a typedef name could refer to
+    if quals.contains(&TyQual::Restrict) && !matches!(ty.kind, TyKind::Ptr(_) | TyKind::Typedef(_))
+    {
+        bail!(cursor.error("`restrict` requires a pointer type"));
+    }
+    let mut unique = Vec::new();
+    for qual in quals {
+        if !unique.contains(&qual) {
+            unique.push(qual);
+        }
+    }
+    let kind = TyKind::Qual(QualTy {
+        quals: unique,
+        ty: Box::new(ty),
+    });
+    Ok(Ty::new(kind, span))
+}
+
+/// Parse a type specifier, e.g. `unsigned int`, `const char` or `struct my_struct`
+fn munch_ty_spec(cursor: &mut TokenCursor<'_>) -> Result<Ty> {
+    let specs = munch_spec_quals(cursor)?;
+    if !specs.align.is_empty() {
+        bail!(cursor.error("unexpected `_Alignas`"));
+    }
+    Ok(attr_ty(specs.ty, specs.attrs))
+}
+
+/// Parse the specifiers of a struct member, which unlike a type specifier may include
+/// alignment specifiers and attributes for the member, e.g. `_Alignas(16) unsigned char`
+fn munch_spec_quals(cursor: &mut TokenCursor<'_>) -> Result<DeclSpecs> {
+    let specs = munch_decl_specs(cursor)?;
+    if let Some(storage) = specs.storage {
+        bail!(cursor.error(format!("unexpected `{}`", storage.to_kw().to_str())));
+    }
+    if specs.is_inline {
+        bail!(cursor.error("unexpected `inline`"));
+    }
+    Ok(specs)
+}
+
+/// Returns whether a keyword is one of the specifiers making up an arithmetic type
+fn is_basic_ty_kw(kw: Kw) -> bool {
+    matches!(
+        kw,
+        Kw::Void
+            | Kw::Bool
+            | Kw::Char
+            | Kw::Short
+            | Kw::Int
+            | Kw::Int128
+            | Kw::Long
+            | Kw::Signed
+            | Kw::Unsigned
+            | Kw::Float
+            | Kw::Double
+            | Kw::BuiltinVaList
+    )
+}
+
+/// Resolve a list of arithmetic type specifiers, which may be written in any order
+/// e.g. `unsigned long long int`, `long unsigned` or `signed`
+fn basic_ty_from_kws(cursor: &TokenCursor<'_>, kws: &[Kw]) -> Result<BasicTy> {
+    // Gathering the keywords in a fixed order leaves a single spelling for each
+    // combination, e.g. `int long unsigned` and `unsigned long int` both become
+    // `int long unsigned`. The order is spelled out rather than taken from `Kw`, so
+    // adding a keyword can't change it.
+    const ORDER: [Kw; 12] = [
+        Kw::Bool,
+        Kw::BuiltinVaList,
+        Kw::Char,
+        Kw::Double,
+        Kw::Float,
+        Kw::Int,
+        Kw::Int128,
+        Kw::Long,
+        Kw::Short,
+        Kw::Signed,
+        Kw::Unsigned,
+        Kw::Void,
+    ];
+    let sorted = ORDER
+        .iter()
+        .flat_map(|&kw| kws.iter().copied().filter(move |&other| other == kw))
+        .collect::<Vec<_>>();
+    let ty = match *sorted.as_slice() {
+        [Kw::Void] => BasicTy::Void,
+        [Kw::Bool] => BasicTy::Bool,
+        [Kw::Char] => BasicTy::Char,
+        [Kw::Char, Kw::Signed] => BasicTy::SignedChar,
+        [Kw::Char, Kw::Unsigned] => BasicTy::UnsignedChar,
+        [Kw::Short]
+        | [Kw::Short, Kw::Signed]
+        | [Kw::Int, Kw::Short]
+        | [Kw::Int, Kw::Short, Kw::Signed] => BasicTy::Short,
+        [Kw::Short, Kw::Unsigned] | [Kw::Int, Kw::Short, Kw::Unsigned] => BasicTy::UnsignedShort,
+        [Kw::Int] | [Kw::Signed] | [Kw::Int, Kw::Signed] => BasicTy::Int,
+        [Kw::Unsigned] | [Kw::Int, Kw::Unsigned] => BasicTy::UnsignedInt,
+        [Kw::Long]
+        | [Kw::Long, Kw::Signed]
+        | [Kw::Int, Kw::Long]
+        | [Kw::Int, Kw::Long, Kw::Signed] => BasicTy::Long,
+        [Kw::Long, Kw::Unsigned] | [Kw::Int, Kw::Long, Kw::Unsigned] => BasicTy::UnsignedLong,
+        [Kw::Long, Kw::Long]
+        | [Kw::Long, Kw::Long, Kw::Signed]
+        | [Kw::Int, Kw::Long, Kw::Long]
+        | [Kw::Int, Kw::Long, Kw::Long, Kw::Signed] => BasicTy::LongLong,
+        [Kw::Long, Kw::Long, Kw::Unsigned] | [Kw::Int, Kw::Long, Kw::Long, Kw::Unsigned] => {
+            BasicTy::UnsignedLongLong
+        }
+        [Kw::Int128] | [Kw::Int128, Kw::Signed] => BasicTy::Int128,
+        [Kw::Int128, Kw::Unsigned] => BasicTy::UnsignedInt128,
+        [Kw::Float] => BasicTy::Float,
+        [Kw::Double] => BasicTy::Double,
+        [Kw::Double, Kw::Long] => BasicTy::LongDouble,
+        [Kw::BuiltinVaList] => BasicTy::VaList,
+        [] => bail!(cursor.expected("type")),
+        _ => {
+            let spelling = kws.iter().map(|kw| kw.to_str()).collect::<Vec<_>>();
+            bail!(cursor.error(format!("invalid type specifiers `{}`", spelling.join(" "))));
+        }
+    };
+    Ok(ty)
+}
+
+/// Parse a struct or union specifier, which either refers to a tag or defines the members
+/// e.g. `struct my_struct`, `union { int i; char c; }` or `struct node { struct node *next; }`
+fn munch_struct_spec(cursor: &mut TokenCursor<'_>) -> Result<Ty> {
+    let start = cursor.idx;
+    let mut new_cursor = cursor.clone();
+    let kind = match new_cursor.peek() {
+        Some(Token::Kw(Kw::Struct)) => StructKind::Struct,
+        Some(Token::Kw(Kw::Union)) => StructKind::Union,
+        _ => bail!(new_cursor.expected("`struct` or `union`")),
+    };
+    new_cursor.advance(1);
+    let attrs_start = new_cursor.idx;
+    let mut attrs = munch_attrs(&mut new_cursor)?;
+    let name = munch_ident(&mut new_cursor).ok();
+    let ty = match (new_cursor.peek(), name) {
+        (Some(Token::Punct(Punct::LBrace)), name) => {
+            new_cursor.advance(1);
+            let mut members = Vec::new();
+            loop {
+                match new_cursor.peek() {
+                    Some(Token::Punct(Punct::RBrace)) => {
+                        new_cursor.advance(1);
+                        break;
+                    }
+                    Some(Token::Kw(Kw::StaticAssert)) => {
+                        let static_assert = munch_static_assert(&mut new_cursor)?;
+                        members.push(StructMember::StaticAssert(static_assert));
+                    }
+                    _ => members.extend(
+                        munch_struct_fields(&mut new_cursor)?
+                            .into_iter()
+                            .map(StructMember::Field),
+                    ),
+                }
+            }
+            // GNU attributes may also follow the closing brace
+            attrs.append(&mut munch_attrs(&mut new_cursor)?);
+            TyKind::StructDefn(StructDefn {
+                attrs,
+                kind,
+                name,
+                members,
+            })
+        }
+        (_, Some(name)) => {
+            if !attrs.is_empty() {
+                new_cursor.warn_at(attrs_start, "attributes ignored on a struct reference");
+            }
+            TyKind::Struct(StructTy { kind, name })
+        }
+        (_, None) => bail!(new_cursor.expected("identifier or `{`")),
+    };
+    let span = new_cursor.span_from(start);
+    cursor.replace(new_cursor);
+    Ok(Ty::new(ty, span))
+}
+
+/// Parse a member declaration inside a struct or union, which may declare several fields
+/// e.g. `int x, *y;`, `unsigned int flags : 3;` or an anonymous `union { int i; char c; };`
+fn munch_struct_fields(cursor: &mut TokenCursor<'_>) -> Result<Vec<StructField>> {
+    let DeclSpecs {
+        align,
+        attrs: spec_attrs,
+        ty: spec,
+        ..
+    } = munch_spec_quals(cursor)?;
+
+    // Anonymous struct or union member
+    if let (TyKind::StructDefn(StructDefn { name: None, .. }), Some(Token::Punct(Punct::Semi))) =
+        (&spec.kind, cursor.peek())
+    {
+        cursor.advance(1);
+        return Ok(vec![StructField {
+            attrs: spec_attrs,
+            align,
+            ty: spec,
+            ident: None,
+            bits: None,
+        }]);
+    }
+
+    let mut fields = Vec::new();
+    loop {
+        let start = cursor.idx;
+        let (ty, ident, mut attrs) = munch_declarator(cursor, spec.clone(), DeclaratorCtx::Other)?;
+        let bits = match cursor.peek() {
+            Some(Token::Punct(Punct::Colon)) => {
+                cursor.advance(1);
+                let bits = munch_cond_expr(cursor)?;
+                attrs.append(&mut munch_attrs(cursor)?);
+                Some(bits)
+            }
+            _ if ident.is_none() => bail!(cursor.expected("identifier or `:`")),
+            _ => None,
+        };
+        if bits.is_some() && !align.is_empty() {
+            bail!(cursor.error_at(start, "`_Alignas` cannot be used on a bit-field"));
+        }
+        fields.push(StructField {
+            attrs: [spec_attrs.clone(), attrs].concat(),
+            align: align.clone(),
+            ty,
+            ident,
+            bits,
+        });
+        match cursor.peek() {
+            Some(Token::Punct(Punct::Comma)) => cursor.advance(1),
+            Some(Token::Punct(Punct::Semi)) => {
+                cursor.advance(1);
+                break;
+            }
+            _ => bail!(cursor.expected("`,` or `;`")),
+        }
+    }
+    Ok(fields)
+}
+
+/// Parse an enum specifier, which either refers to a tag or defines the enumerators
+/// e.g. `enum color`, `enum { RED, GREEN = 5, BLUE, }` or `enum e : char { A }`
+fn munch_enum_spec(cursor: &mut TokenCursor<'_>) -> Result<Ty> {
+    let start = cursor.idx;
+    let mut new_cursor = cursor.clone();
+    match new_cursor.peek() {
+        Some(Token::Kw(Kw::Enum)) => new_cursor.advance(1),
+        _ => bail!(new_cursor.expected("`enum`")),
+    }
+    let attrs_start = new_cursor.idx;
+    let mut attrs = munch_attrs(&mut new_cursor)?;
+    let name = munch_ident(&mut new_cursor).ok();
+
+    // C23 fixed underlying type
+    let mut base = None;
+    if let Some(Token::Punct(Punct::Colon)) = new_cursor.peek() {
+        let mut base_cursor = new_cursor.clone();
+        base_cursor.advance(1);
+        if let Ok(ty) = munch_ty_spec(&mut base_cursor) {
+            base = Some(Box::new(ty));
+            new_cursor.replace(base_cursor);
+        }
+    }
+
+    let ty = match (new_cursor.peek(), name) {
+        (Some(Token::Punct(Punct::LBrace)), name) => {
+            new_cursor.advance(1);
+            let mut variants = Vec::new();
+            loop {
+                if let Some(Token::Punct(Punct::RBrace)) = new_cursor.peek() {
+                    new_cursor.advance(1);
+                    break;
+                }
+                let ident = munch_ident(&mut new_cursor)?;
+                let attrs = munch_attrs(&mut new_cursor)?;
+                let mut value = None;
+                if let Some(Token::Punct(Punct::Eq)) = new_cursor.peek() {
+                    new_cursor.advance(1);
+                    value = Some(munch_cond_expr(&mut new_cursor)?);
+                }
+                new_cursor.declare(&ident, false);
+                variants.push(Enumerator {
+                    attrs,
+                    ident,
+                    value,
+                });
+                match new_cursor.peek() {
+                    Some(Token::Punct(Punct::Comma)) => new_cursor.advance(1),
+                    Some(Token::Punct(Punct::RBrace)) => {
+                        new_cursor.advance(1);
+                        break;
+                    }
+                    _ => bail!(new_cursor.expected("`,` or `}`")),
+                }
+            }
+            if variants.is_empty() {
+                bail!(new_cursor.expected("enumerator"));
+            }
+            // GNU attributes may also follow the closing brace
+            attrs.append(&mut munch_attrs(&mut new_cursor)?);
+            TyKind::EnumDefn(EnumDefn {
+                attrs,
+                name,
+                base,
+                variants,
+            })
+        }
+        (_, Some(_)) if base.is_some() => bail!(new_cursor.expected("`{`")),
+        (_, Some(name)) => {
+            if !attrs.is_empty() {
+                new_cursor.warn_at(attrs_start, "attributes ignored on an enum reference");
+            }
+            TyKind::Enum(EnumTy { name })
+        }
+        (_, None) => bail!(new_cursor.expected("identifier or `{`")),
+    };
+    let span = new_cursor.span_from(start);
+    cursor.replace(new_cursor);
+    Ok(Ty::new(ty, span))
+}
+
+/// A type derived by a declarator from the type it is given, e.g. the pointer in `*p`
+/// or the array in `a[3]`. A declarator is parsed into a list of derivations before
+/// any are applied, since the suffixes after a nested declarator bind tighter than
+/// anything inside it.
+enum Derivation {
+    /// A `*` followed by any qualifiers and attributes, which end at `end`
+    Ptr {
+        star: Span,
+        quals: Vec<TyQual>,
+        attrs: Vec<Attr>,
+        end: Span,
+    },
+    /// An array suffix. The derived type extends to `end`, and is checked at the token
+    /// `err_idx` once the element type is known.
+    Array {
+        length: Option<Box<Expr>>,
+        quals: Vec<TyQual>,
+        is_static: bool,
+        is_star: bool,
+        end: Span,
+        err_idx: usize,
+    },
+    /// A function suffix, which is checked like an array suffix
+    Fn {
+        params: Vec<Param>,
+        variadic: bool,
+        style: ParamStyle,
+        end: Span,
+        err_idx: usize,
+    },
+}
+
+/// Parse any number of pointer stars at the start of a declarator, each with their own
+/// qualifiers and attributes
+/// e.g. `*const *` or `* __attribute__((aligned(8)))`
+fn munch_ty_ptrs(cursor: &mut TokenCursor<'_>) -> Result<Vec<Derivation>> {
+    let mut derivations = Vec::new();
+    while let Some(Token::Punct(Punct::Star)) = cursor.peek() {
+        cursor.advance(1);
+        let star = cursor.prev_span();
+        let mut quals = Vec::new();
+        let mut attrs = Vec::new();
+        loop {
+            match cursor.peek() {
+                Some(&Token::Kw(kw)) if TyQual::from_kw(kw).is_some() => {
+                    quals.extend(TyQual::from_kw(kw).filter(|qual| !quals.contains(qual)));
+                    cursor.advance(1);
+                }
+                _ if is_attr_start(cursor) => attrs.append(&mut munch_attrs(cursor)?),
+                _ => break,
+            }
+        }
+        derivations.push(Derivation::Ptr {
+            star,
+            quals,
+            attrs,
+            end: cursor.prev_span(),
+        });
+    }
+    Ok(derivations)
+}
+
+/// Parse any array or function suffixes following a declarator, returning them in the
+/// order they apply, which is last suffix first
+/// e.g. `[3][4]`, `[N + 1]`, `[]`, `[static 10]` or `(int, char *)`
+fn munch_ty_suffixes(cursor: &mut TokenCursor<'_>) -> Result<Vec<Derivation>> {
+    let mut derivation = match cursor.peek() {
+        // `[[` starts the attributes of the declarator rather than another dimension
+        Some(Token::Punct(Punct::LBrack)) if !is_attr_start(cursor) => {
+            cursor.advance(1);
+            // Parameters may have `static` and qualifiers before the length, in any order
+            let mut quals = Vec::new();
+            let mut is_static = false;
+            loop {
+                match cursor.peek() {
+                    Some(Token::Kw(Kw::Static)) if !is_static => {
+                        is_static = true;
+                        cursor.advance(1);
+                    }
+                    Some(&Token::Kw(kw)) if TyQual::from_kw(kw).is_some() => {
+                        quals.extend(TyQual::from_kw(kw).filter(|qual| !quals.contains(qual)));
+                        cursor.advance(1);
+                    }
+                    _ => break,
+                }
+            }
+            let length_start = cursor.idx;
+            let mut is_star = false;
+            let length = match cursor.npeek() {
+                [Token::Punct(Punct::Star), Token::Punct(Punct::RBrack), ..] if !is_static => {
+                    is_star = true;
+                    cursor.advance(1);
+                    None
+                }
+                [Token::Punct(Punct::RBrack), ..] if !is_static => None,
+                _ => Some(Box::new(munch_cond_expr(cursor)?)),
+            };
+            if let Some(ExprKind::Const(ConstExpr { lit: Lit::Int(0) })) =
+                length.as_ref().map(|length| &length.kind)
+            {
+                if !cursor.options.gnu_extensions {
+                    bail!(cursor.error_at(length_start, "zero-length arrays are a GNU extension"));
+                }
+            }
+            munch_punct(cursor, Punct::RBrack)?;
+            Derivation::Array {
+                length,
+                quals,
+                is_static,
+                is_star,
+                end: Span::default(),
+                err_idx: 0,
+            }
+        }
+        Some(Token::Punct(Punct::LParen)) => {
+            cursor.advance(1);
+            let (params, variadic, style) = munch_params(cursor)?;
+            Derivation::Fn {
+                params,
+                variadic,
+                style,
+                end: Span::default(),
+                err_idx: 0,
+            }
+        }
+        _ => return Ok(Vec::new()),
+    };
+    // Later suffixes belong to the element or return type, `[3][4]` is 3 arrays of 4
+    let mut derivations = munch_ty_suffixes(cursor)?;
+    if let Derivation::Array { end, err_idx, .. } | Derivation::Fn { end, err_idx, .. } =
+        &mut derivation
+    {
+        *end = cursor.prev_span();
+        *err_idx = cursor.idx;
+    }
+    derivations.push(derivation);
+    Ok(derivations)
+}
+
+/// Apply the derivations of a declarator, in order, to the type from its specifiers
+fn derive_ty(cursor: &TokenCursor<'_>, mut ty: Ty, derivations: Vec<Derivation>) -> Result<Ty> {
+    for derivation in derivations {
+        ty = match derivation {
+            Derivation::Ptr {
+                star,
+                quals,
+                attrs,
+                end,
+            } => {
+                let span = ty.span.join(star);
+                let mut ty = Ty::new(TyKind::Ptr(PtrTy { ty: Box::new(ty) }), span);
+                if !quals.is_empty() {
+                    let span = ty.span.join(end);
+                    let kind = TyKind::Qual(QualTy {
+                        quals,
+                        ty: Box::new(ty),
+                    });
+                    ty = Ty::new(kind, span);
+                }
+                if !attrs.is_empty() {
+                    let span = ty.span.join(end);
+                    let kind = TyKind::Attr(AttrTy {
+                        attrs,
+                        ty: Box::new(ty),
+                    });
+                    ty = Ty::new(kind, span);
+                }
+                ty
+            }
+            Derivation::Array {
+                length,
+                quals,
+                is_static,
+                is_star,
+                end,
+                err_idx,
+            } => {
+                match ty.kind {
+                    TyKind::Basic(BasicTy::Void) => {
+                        bail!(cursor.error_at(err_idx, "cannot have array of void"))
+                    }
+                    TyKind::Fn(_) => {
+                        bail!(cursor.error_at(err_idx, "cannot have array of functions"))
+                    }
+                    _ => {}
+                }
+                let span = ty.span.join(end);
+                let kind = TyKind::Array(ArrayTy {
+                    ty: Box::new(ty),
+                    length,
+                    quals,
+                    is_static,
+                    is_star,
+                });
+                Ty::new(kind, span)
+            }
+            Derivation::Fn {
+                params,
+                variadic,
+                style,
+                end,
+                err_idx,
+            } => {
+                match ty.kind {
+                    TyKind::Array(_) => {
+                        bail!(cursor.error_at(err_idx, "function cannot return array"))
+                    }
+                    TyKind::Fn(_) => {
+                        bail!(cursor.error_at(err_idx, "function cannot return function"))
+                    }
+                    _ => {}
+                }
+                let span = ty.span.join(end);
+                let kind = TyKind::Fn(FnTy {
+                    ret: Box::new(ty),
+                    params,
+                    variadic,
+                    style,
+                });
+                Ty::new(kind, span)
+            }
+        };
+    }
+    Ok(ty)
+}
+
+/// Parse the parameters of a function declarator after the opening `(`, whether they
+/// end with `...`, and how they were written
+/// e.g. `void)`, `)`, `int argc, char **argv)`, `const char *fmt, ...)` or `a, b)`
+fn munch_params(cursor: &mut TokenCursor<'_>) -> Result<(Vec<Param>, bool, ParamStyle)> {
+    let mut params = Vec::new();
+    let mut variadic = false;
+    match cursor.npeek() {
+        // Special case: void parameter list
+        [Token::Kw(Kw::Void), Token::Punct(Punct::RParen), ..] => {
+            cursor.advance(2);
+            return Ok((params, variadic, ParamStyle::Prototype));
+        }
+        [Token::Punct(Punct::RParen), ..] => {
+            cursor.advance(1);
+            return Ok((params, variadic, ParamStyle::Empty));
+        }
+        [Token::Ident(ident), Token::Punct(Punct::Comma | Punct::RParen), ..]
+            if !cursor.is_typedef(ident) =>
+        {
+            return Ok((munch_ident_list(cursor)?, variadic, ParamStyle::OldStyle));
+        }
+        _ => {}
+    }
+    // Parameter names are only visible until the end of the parameter list
+    cursor.push_scope();
+    loop {
+        let start = cursor.idx;
+        if !is_decl_start(cursor) {
+            bail!(cursor.expected("parameter"));
+        }
+        let specs = munch_decl_specs(cursor)?;
+        // `register` only forbids taking the address, so there is nothing to record
+        match specs.storage {
+            None | Some(StorageClass::Register) => {}
+            Some(storage) => bail!(cursor.error(format!(
+                "parameter cannot be `{}`",
+                storage.to_kw().to_str()
+            ))),
+        }
+        if specs.is_inline {
+            bail!(cursor.error("`inline` can only appear on functions"));
+        }
+        if !specs.align.is_empty() {
+            bail!(cursor.error("`_Alignas` cannot be used on a parameter"));
+        }
+        let (ty, ident, attrs) = munch_declarator(cursor, specs.ty, DeclaratorCtx::Param)?;
+        if let Some(ident) = &ident {
+            cursor.declare(ident, false);
+        }
+        let span = cursor.span_from(start);
+        params.push(Param {
+            attrs: [specs.attrs, attrs].concat(),
+            ident,
+            ty,
+            span,
+        });
+        match *cursor.npeek() {
+            [Token::Punct(Punct::Comma), Token::Punct(Punct::Ellipsis), Token::Punct(Punct::RParen), ..] =>
+            {
+                cursor.advance(3);
+                variadic = true;
+                break;
+            }
+            [Token::Punct(Punct::Comma), ..] => cursor.advance(1),
+            [Token::Punct(Punct::RParen), ..] => {
+                cursor.advance(1);
+                break;
+            }
+            _ => bail!(cursor.expected("`,` or `)`")),
+        }
+    }
+    cursor.pop_scope();
+    Ok((params, variadic, ParamStyle::Prototype))
+}
+
+/// Parse the identifier list of an old-style function declarator after the opening `(`.
+/// Each parameter is `int` until the definition declares otherwise.
+/// e.g. `a, b)`
+fn munch_ident_list(cursor: &mut TokenCursor<'_>) -> Result<Vec<Param>> {
+    let mut params = Vec::new();
+    loop {
+        let start = cursor.idx;
+        let ident = munch_ident(cursor)?;
+        if params
+            .iter()
+            .any(|param: &Param| param.ident.as_ref() == Some(&ident))
+        {
+            bail!(cursor.error_at(start, format!("duplicate parameter `{ident}`")));
+        }
+        let span = cursor.span_from(start);
+        let ty = Ty::new(TyKind::Basic(BasicTy::Int), span);
+        params.push(Param {
+            attrs: Vec::new(),
+            ident: Some(ident),
+            ty,
+            span,
+        });
+        match cursor.peek() {
+            Some(Token::Punct(Punct::Comma)) => cursor.advance(1),
+            Some(Token::Punct(Punct::RParen)) => {
+                cursor.advance(1);
+                break;
+            }
+            _ => bail!(cursor.expected("`,` or `)`")),
+        }
+    }
+    Ok(params)
+}
+
+/// Returns whether the `(` at the cursor opens a nested declarator like `(*fp)`, rather
+/// than the parameter list of an abstract function declarator like `(int, char *)`.
+/// Attributes may start either, so the check looks past them.
+fn is_nested_declarator(cursor: &TokenCursor<'_>) -> bool {
+    if let [Token::Punct(Punct::LParen), ..] = cursor.npeek() {
+        let mut new_cursor = cursor.clone();
+        new_cursor.advance(1);
+        if is_attr_start(&new_cursor) {
+            return match munch_attrs(&mut new_cursor) {
+                Ok(_) => match new_cursor.npeek() {
+                    [Token::Punct(Punct::Star | Punct::LParen), ..] => true,
+                    [Token::Ident(ident), ..] => !new_cursor.is_typedef(ident),
+                    _ => false,
+                },
+                Err(_) => false,
+            };
+        }
+    }
+    match cursor.npeek() {
+        [Token::Punct(Punct::LParen), Token::Punct(Punct::Star | Punct::LParen), ..] => true,
+        [Token::Punct(Punct::LParen), Token::Ident(ident), ..] => !cursor.is_typedef(ident),
+        _ => false,
+    }
+}
+
+/// Where a declarator appears, which decides the derivations it may have
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DeclaratorCtx {
+    /// A parameter, whose outermost array may have `static` and qualifiers
+    Param,
+    /// The declarator of a function definition, whose outermost function may have an
+    /// identifier list
+    FnDefn,
+    Other,
+}
+
+/// Parse a declarator, which wraps a type in pointers, arrays and functions and names
+/// the declared identifier. The identifier is missing from abstract declarators, as used
+/// in type names and unnamed parameters. Also returns the attributes following the
+/// identifier or the whole declarator, which apply to whatever is declared.
+///
+/// e.g. `*argv[]`, `(*fp)(int, char *)`, `(*)[10]` or `x __attribute__((unused))`
+fn munch_declarator(
+    cursor: &mut TokenCursor<'_>,
+    ty: Ty,
+    ctx: DeclaratorCtx,
+) -> Result<(Ty, Option<String>, Vec<Attr>)> {
+    let start = cursor.idx;
+    let (derivations, ident, attrs) = munch_derivations(cursor)?;
+    check_derivations(cursor, &derivations, ctx, start)?;
+    let ty = derive_ty(cursor, ty, derivations)?;
+    Ok((ty, ident, attrs))
+}
+
+/// Check that the derivations of a declarator starting at `start` are allowed where it
+/// appears. Only the outermost derivation is adjusted for a parameter or defined by a
+/// function definition, so the forms specific to those only apply to it.
+fn check_derivations(
+    cursor: &TokenCursor<'_>,
+    derivations: &[Derivation],
+    ctx: DeclaratorCtx,
+    start: usize,
+) -> Result<()> {
+    for (i, derivation) in derivations.iter().enumerate() {
+        let is_outermost = i + 1 == derivations.len();
+        match derivation {
+            Derivation::Array {
+                quals, is_static, ..
+            } if (*is_static || !quals.is_empty())
+                && !(is_outermost && ctx == DeclaratorCtx::Param) =>
+            {
+                bail!(cursor.error_at(
+                    start,
+                    "`static` and qualifiers in array brackets are only allowed in the \
+                     outermost array of a parameter"
+                ))
+            }
+            Derivation::Array { is_star: true, .. } if ctx != DeclaratorCtx::Param => {
+                bail!(cursor.error_at(start, "`[*]` is only allowed in parameters"))
+            }
+            Derivation::Fn {
+                style: ParamStyle::OldStyle,
+                ..
+            } if !(is_outermost && ctx == DeclaratorCtx::FnDefn) => bail!(cursor.error_at(
+                start,
+                "parameter names without types are only allowed in function definitions"
+            )),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Parse a declarator into its derivations, in the order they apply to the type it is
+/// given, along with its identifier and attributes
+fn munch_derivations(
+    cursor: &mut TokenCursor<'_>,
+) -> Result<(Vec<Derivation>, Option<String>, Vec<Attr>)> {
+    let mut derivations = munch_ty_ptrs(cursor)?;
+    if is_nested_declarator(cursor) {
+        cursor.advance(1);
+        // Attributes just inside the parentheses apply to what is declared, like those
+        // after the identifier, e.g. `(__attribute__((noreturn)) *handler)`
+        let mut attrs = munch_attrs(cursor)?;
+        let (mut inner, ident, mut inner_attrs) = munch_derivations(cursor)?;
+        attrs.append(&mut inner_attrs);
+        munch_punct(cursor, Punct::RParen)?;
+        // The suffixes after the parentheses bind tighter than anything inside them
+        derivations.append(&mut munch_ty_suffixes(cursor)?);
+        derivations.append(&mut inner);
+        attrs.append(&mut munch_attrs(cursor)?);
+        return Ok((derivations, ident, attrs));
+    }
+    let ident = munch_ident(cursor).ok();
+    let mut attrs = match ident {
+        Some(_) => munch_attrs(cursor)?,
+        None => Vec::new(),
+    };
+    derivations.append(&mut munch_ty_suffixes(cursor)?);
+    attrs.append(&mut munch_attrs(cursor)?);
+    Ok((derivations, ident, attrs))
+}
+
+/// Parse a type name, which is a type without an identifier, as used in casts and `sizeof`
+/// e.g. `struct my_struct **` or `int (*)[10]`
+fn munch_ty_name(cursor: &mut TokenCursor<'_>) -> Result<Ty> {
+    let mut new_cursor = cursor.clone();
+    let ty = munch_ty_spec(&mut new_cursor)?;
+    let (ty, ident, attrs) = munch_declarator(&mut new_cursor, ty, DeclaratorCtx::Other)?;
+    if ident.is_some() {
+        bail!(new_cursor.error("unexpected identifier in type name"));
+    }
+    cursor.replace(new_cursor);
+    Ok(attr_ty(ty, attrs))
+}
+
+/// Apply attributes to a type, which is left alone if there are none
+fn attr_ty(ty: Ty, attrs: Vec<Attr>) -> Ty {
+    if attrs.is_empty() {
+        return ty;
+    }
+    let span = attrs
+        .iter()
+        .fold(ty.span, |span, attr| span.join(attr.span));
+    let kind = TyKind::Attr(AttrTy {
+        attrs,
+        ty: Box::new(ty),
+    });
+    Ty::new(kind, span)
+}
+
+/// Parse a parenthesized type name `( type-name )`, or nothing if the parentheses do
+/// not begin with a type. Used to resolve the ambiguity between casts and parenthesized
+/// expressions.
+fn munch_paren_ty_name(cursor: &mut TokenCursor<'_>) -> Result<Option<Ty>> {
+    let mut new_cursor = cursor.clone();
+    if munch_punct(&mut new_cursor, Punct::LParen).is_err() || !is_decl_start(&new_cursor) {
+        return Ok(None);
+    }
+    let ty = munch_ty_name(&mut new_cursor)?;
+    munch_punct(&mut new_cursor, Punct::RParen)?;
+    cursor.replace(new_cursor);
+    Ok(Some(ty))
+}
+
+/// Parse the declarator of a typed identifier, given the type from its specifiers, and
+/// return the type, the identifier and its attributes. Typed identifiers seem to be a
+/// common pattern throughout C, used in variable declarations, function definitions, etc.
+///
+/// e.g. `ptr[10]` after `struct my_struct` => length 10 array of struct my_struct
+fn munch_typed_ident(
+    cursor: &mut TokenCursor<'_>,
+    ty: Ty,
+    ctx: DeclaratorCtx,
+) -> Result<(Ty, String, Vec<Attr>)> {
+    let mut new_cursor = cursor.clone();
+
+    let (ty, ident, attrs) = munch_declarator(&mut new_cursor, ty, ctx)?;
+    let Some(ident) = ident else {
+        bail!(new_cursor.expected("identifier"));
+    };
+
+    cursor.replace(new_cursor);
+    Ok((ty, ident, attrs))
+}
+
+/// Consume the given punctuator
+fn munch_punct(cursor: &mut TokenCursor<'_>, punct: Punct) -> Result<()> {
+    match cursor.peek() {
+        Some(&Token::Punct(p)) if p == punct => {
+            cursor.advance(1);
+            Ok(())
+        }
+        _ => bail!(cursor.expected(format!("`{}`", punct.to_str()))),
+    }
+}
+
+/// Consume an identifier, returning its name
+fn munch_ident(cursor: &mut TokenCursor<'_>) -> Result<String> {
+    match cursor.peek() {
+        Some(Token::Ident(ident)) => {
+            let ident = ident.clone();
+            cursor.advance(1);
+            Ok(ident)
+        }
+        _ => bail!(cursor.expected("identifier")),
+    }
+}
+
+/// Parse a primary expression: an identifier, a literal, a parenthesized expression, a
+/// generic selection, a statement expression, or a compiler builtin
+fn munch_primary_expr(cursor: &mut TokenCursor<'_>) -> Result<Expr> {
+    let start = cursor.idx;
+    match cursor.peek() {
+        Some(Token::Ident(ident)) if ident.starts_with("__builtin_") => {
+            let kind = munch_builtin(cursor)?;
+            Ok(Expr::new(kind, cursor.span_from(start)))
+        }
+        Some(Token::Ident(ident)) => {
+            let ident = ident.clone();
+            cursor.advance(1);
+            Ok(Expr::new(
+                ExprKind::Ident(IdentExpr { ident }),
+                cursor.span_from(start),
+            ))
+        }
+        Some(Token::Lit(lit)) => {
+            let lit = lit.clone();
+            cursor.advance(1);
+            Ok(Expr::new(
+                ExprKind::Const(ConstExpr { lit }),
+                cursor.span_from(start),
+            ))
+        }
+        Some(Token::Punct(Punct::LParen))
+            if matches!(cursor.npeek().get(1), Some(Token::Punct(Punct::LBrace))) =>
+        {
+            cursor.require_gnu("statement expressions are a GNU extension")?;
+            cursor.advance(1);
+            let stmts = munch_block_items(cursor)?;
+            munch_punct(cursor, Punct::RParen)?;
+            Ok(Expr::new(
+                ExprKind::Stmt(StmtExpr { stmts }),
+                cursor.span_from(start),
+            ))
+        }
+        Some(Token::Punct(Punct::LParen)) => {
+            cursor.advance(1);
+            let expr = munch_expr(cursor)?;
+            munch_punct(cursor, Punct::RParen)?;
+            Ok(Expr::new(
+                ExprKind::Paren(ParenExpr {
+                    expr: Box::new(expr),
+                }),
+                cursor.span_from(start),
+            ))
+        }
+        Some(Token::Kw(Kw::Generic)) => {
+            cursor.advance(1);
+            let generic = munch_generic(cursor)?;
+            Ok(Expr::new(
+                ExprKind::Generic(generic),
+                cursor.span_from(start),
+            ))
+        }
+        Some(Token::Kw(Kw::BuiltinVaStart)) => {
+            cursor.advance(1);
+            munch_punct(cursor, Punct::LParen)?;
+            let ap = munch_assn_expr(cursor)?;
+            munch_punct(cursor, Punct::Comma)?;
+            let last = munch_assn_expr(cursor)?;
+            munch_punct(cursor, Punct::RParen)?;
+            Ok(Expr::new(
+                ExprKind::VaStart(VaStartExpr {
+                    ap: Box::new(ap),
+                    last: Box::new(last),
+                }),
+                cursor.span_from(start),
+            ))
+        }
+        Some(Token::Kw(Kw::BuiltinVaArg)) => {
+            cursor.advance(1);
+            munch_punct(cursor, Punct::LParen)?;
+            let ap = munch_assn_expr(cursor)?;
+            munch_punct(cursor, Punct::Comma)?;
+            let ty = munch_ty_name(cursor)?;
+            munch_punct(cursor, Punct::RParen)?;
+            Ok(Expr::new(
+                ExprKind::VaArg(VaArgExpr {
+                    ap: Box::new(ap),
+                    ty,
+                }),
+                cursor.span_from(start),
+            ))
+        }
+        Some(Token::Kw(Kw::BuiltinVaEnd)) => {
+            cursor.advance(1);
+            munch_punct(cursor, Punct::LParen)?;
+            let ap = munch_assn_expr(cursor)?;
+            munch_punct(cursor, Punct::RParen)?;
+            Ok(Expr::new(
+                ExprKind::VaEnd(VaEndExpr { ap: Box::new(ap) }),
+                cursor.span_from(start),
+            ))
+        }
+        Some(Token::Kw(Kw::BuiltinVaCopy)) => {
+            cursor.advance(1);
+            munch_punct(cursor, Punct::LParen)?;
+            let dest = munch_assn_expr(cursor)?;
+            munch_punct(cursor, Punct::Comma)?;
+            let src = munch_assn_expr(cursor)?;
+            munch_punct(cursor, Punct::RParen)?;
+            Ok(Expr::new(
+                ExprKind::VaCopy(VaCopyExpr {
+                    dest: Box::new(dest),
+                    src: Box::new(src),
+                }),
+                cursor.span_from(start),
+            ))
+        }
+        // A type name beginning with `typeof` only fails to parse in strict mode
+        Some(&Token::Kw(kw @ (Kw::Typeof | Kw::GnuTypeof))) => {
+            cursor.require_gnu(&format!("`{}` is a GNU extension", kw.to_str()))?;
+            bail!(cursor.expected("expression"))
+        }
+        _ => bail!(cursor.expected("expression")),
+    }
+}
+
+/// Parse a call to a GNU builtin. Most take expressions like a function call, but a
+/// few take type names.
+/// e.g. `__builtin_expect(x, 0)`, `__builtin_offsetof(struct s, a.b[2])` or
+/// `__builtin_types_compatible_p(int, long)`
+fn munch_builtin(cursor: &mut TokenCursor<'_>) -> Result<ExprKind> {
+    let name = match cursor.peek() {
+        Some(Token::Ident(name)) => name.clone(),
+        _ => bail!(cursor.expected("builtin")),
+    };
+    cursor.require_gnu(&format!("`{name}` is a GNU extension"))?;
+    cursor.advance(1);
+    munch_punct(cursor, Punct::LParen)?;
+    let kind = match name.as_str() {
+        "__builtin_offsetof" => {
+            let ty = munch_ty_name(cursor)?;
+            munch_punct(cursor, Punct::Comma)?;
+            let mut designators = vec![Designator::Field(munch_ident(cursor)?)];
+            loop {
+                match cursor.peek() {
+                    Some(Token::Punct(Punct::Dot)) => {
+                        cursor.advance(1);
+                        designators.push(Designator::Field(munch_ident(cursor)?));
+                    }
+                    Some(Token::Punct(Punct::LBrack)) => {
+                        cursor.advance(1);
+                        designators.push(Designator::Index(munch_expr(cursor)?));
+                        munch_punct(cursor, Punct::RBrack)?;
+                    }
+                    _ => break,
+                }
+            }
+            ExprKind::Offsetof(OffsetofExpr { ty, designators })
+        }
+        "__builtin_types_compatible_p" => {
+            let lhs = munch_ty_name(cursor)?;
+            munch_punct(cursor, Punct::Comma)?;
+            let rhs = munch_ty_name(cursor)?;
+            ExprKind::TypesCompatible(TypesCompatibleExpr {
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            })
+        }
+        _ => {
+            let mut args = Vec::new();
+            while !matches!(cursor.peek(), Some(Token::Punct(Punct::RParen))) {
+                if !args.is_empty() {
+                    munch_punct(cursor, Punct::Comma)?;
+                }
+                args.push(munch_assn_expr(cursor)?);
+            }
+            ExprKind::BuiltinCall(BuiltinCallExpr { name, args })
+        }
+    };
+    munch_punct(cursor, Punct::RParen)?;
+    Ok(kind)
+}
+
+/// Parse the operands of a generic selection after `_Generic`
+/// e.g. `(x, float: sqrtf, long double: sqrtl, default: sqrt)`
+fn munch_generic(cursor: &mut TokenCursor<'_>) -> Result<GenericExpr> {
+    munch_punct(cursor, Punct::LParen)?;
+    let ctrl = munch_assn_expr(cursor)?;
+    munch_punct(cursor, Punct::Comma)?;
+    let mut assocs: Vec<GenericAssoc> = Vec::new();
+    loop {
+        let ty = match cursor.peek() {
+            Some(Token::Kw(Kw::Default)) => {
+                if assocs.iter().any(|assoc| assoc.ty.is_none()) {
+                    bail!(cursor.error("duplicate `default` association"));
+                }
+                cursor.advance(1);
+                None
+            }
+            _ if is_decl_spec_start(cursor) => Some(munch_ty_name(cursor)?),
+            _ => bail!(cursor.expected("type name or `default`")),
+        };
+        munch_punct(cursor, Punct::Colon)?;
+        let expr = munch_assn_expr(cursor)?;
+        assocs.push(GenericAssoc { ty, expr });
+        match cursor.peek() {
+            Some(Token::Punct(Punct::Comma)) => cursor.advance(1),
+            Some(Token::Punct(Punct::RParen)) => {
+                cursor.advance(1);
+                break;
+            }
+            _ => bail!(cursor.expected("`,` or `)`")),
+        }
+    }
+    Ok(GenericExpr {
+        ctrl: Box::new(ctrl),
+        assocs,
+    })
+}
+
+/// Parse the rest of a compound literal starting at `start`, given its parenthesized
+/// type name, along with any postfix operators applied to it. Callers parse the type
+/// name first to tell compound literals from casts, and it is not parsed again, since
+/// it may contain expressions with more type names.
+/// e.g. `{ .x = 1, .y = 2 }` after `(struct point)` or `{ 1, 2, 3 }[i]` after `(int[])`
+fn munch_compound_lit(cursor: &mut TokenCursor<'_>, ty: Ty, start: usize) -> Result<Expr> {
+    let init = munch_init_list(cursor)?;
+    let expr = Expr::new(
+        ExprKind::CompoundLit(CompoundLitExpr { ty, init }),
+        cursor.span_from(start),
+    );
+    munch_postfix_ops(cursor, expr, start)
+}
+
+/// Parse a postfix expression, e.g. `f(x, y)`, `a[i]`, `s.f`, `p->f` or `x++`
+fn munch_postfix_expr(cursor: &mut TokenCursor<'_>) -> Result<Expr> {
+    let start = cursor.idx;
+    // A parenthesized type name here can only begin a compound literal
+    if let Some(ty) = munch_paren_ty_name(cursor)? {
+        return munch_compound_lit(cursor, ty, start);
+    }
+    let expr = munch_primary_expr(cursor)?;
+    munch_postfix_ops(cursor, expr, start)
+}
+
+/// Parse the postfix operators applied to an expression starting at `start`
+fn munch_postfix_ops(cursor: &mut TokenCursor<'_>, mut expr: Expr, start: usize) -> Result<Expr> {
+    loop {
+        match cursor.peek() {
+            Some(Token::Punct(Punct::LParen)) => {
+                cursor.advance(1);
+                let mut params = Vec::new();
+                if let Some(Token::Punct(Punct::RParen)) = cursor.peek() {
+                    cursor.advance(1);
+                } else {
+                    loop {
+                        params.push(munch_assn_expr(cursor)?);
+                        match cursor.peek() {
+                            Some(Token::Punct(Punct::Comma)) => cursor.advance(1),
+                            Some(Token::Punct(Punct::RParen)) => {
+                                cursor.advance(1);
+                                break;
+                            }
+                            _ => bail!(cursor.expected("`,` or `)`")),
+                        }
+                    }
+                }
+                expr = Expr::new(
+                    ExprKind::Call(CallExpr {
+                        fun: Box::new(expr),
+                        params,
+                    }),
+                    cursor.span_from(start),
+                );
+            }
+            Some(Token::Punct(Punct::LBrack)) => {
+                cursor.advance(1);
+                let index = munch_expr(cursor)?;
+                munch_punct(cursor, Punct::RBrack)?;
+                expr = Expr::new(
+                    ExprKind::Index(IndexExpr {
+                        expr: Box::new(expr),
+                        index: Box::new(index),
+                    }),
+                    cursor.span_from(start),
+                );
+            }
+            Some(Token::Punct(Punct::Dot)) => {
+                cursor.advance(1);
+                let field = munch_ident(cursor)?;
+                expr = Expr::new(
+                    ExprKind::Member(MemberExpr {
+                        expr: Box::new(expr),
+                        field,
+                    }),
+                    cursor.span_from(start),
+                );
+            }
+            Some(Token::Punct(Punct::Arrow)) => {
+                cursor.advance(1);
+                let field = munch_ident(cursor)?;
+                expr = Expr::new(
+                    ExprKind::Arrow(ArrowExpr {
+                        expr: Box::new(expr),
+                        field,
+                    }),
+                    cursor.span_from(start),
+                );
+            }
+            Some(Token::Punct(Punct::Plus2)) => {
+                cursor.advance(1);
+                expr = Expr::new(
+                    ExprKind::UnOp(UnOpExpr {
+                        op: UnOp::PostIncr,
+                        expr: Box::new(expr),
+                    }),
+                    cursor.span_from(start),
+                );
+            }
+            Some(Token::Punct(Punct::Dash2)) => {
+                cursor.advance(1);
+                expr = Expr::new(
+                    ExprKind::UnOp(UnOpExpr {
+                        op: UnOp::PostDecr,
+                        expr: Box::new(expr),
+                    }),
+                    cursor.span_from(start),
+                );
+            }
+            _ => break,
+        }
+    }
+    Ok(expr)
+}
+
+/// Parse a prefix unary expression, e.g. `-x`, `++x`, `*p`, `&x`, `sizeof x` or `&&label`
+fn munch_unary_expr(cursor: &mut TokenCursor<'_>) -> Result<Expr> {
+    let start = cursor.idx;
+    let op = match cursor.peek() {
+        Some(Token::Punct(Punct::Plus2)) => UnOp::PreIncr,
+        Some(Token::Punct(Punct::Dash2)) => UnOp::PreDecr,
+        Some(Token::Punct(Punct::Plus)) => UnOp::Pos,
+        Some(Token::Punct(Punct::Dash)) => UnOp::Neg,
+        Some(Token::Punct(Punct::Exclam)) => UnOp::Not,
+        Some(Token::Punct(Punct::Tilde)) => UnOp::BitNot,
+        Some(Token::Punct(Punct::Star)) => {
+            cursor.advance(1);
+            let expr = munch_cast_expr(cursor)?;
+            return Ok(Expr::new(
+                ExprKind::Deref(DerefExpr {
+                    expr: Box::new(expr),
+                }),
+                cursor.span_from(start),
+            ));
+        }
+        Some(Token::Punct(Punct::Amp)) => {
+            cursor.advance(1);
+            let expr = munch_cast_expr(cursor)?;
+            return Ok(Expr::new(
+                ExprKind::Ref(RefExpr {
+                    expr: Box::new(expr),
+                }),
+                cursor.span_from(start),
+            ));
+        }
+        Some(Token::Kw(Kw::Sizeof)) => {
+            cursor.advance(1);
+            let operand_start = cursor.idx;
+            let expr = match munch_paren_ty_name(cursor)? {
+                Some(ty) if matches!(cursor.peek(), Some(Token::Punct(Punct::LBrace))) => {
+                    munch_compound_lit(cursor, ty, operand_start)?
+                }
+                Some(ty) => {
+                    return Ok(Expr::new(
+                        ExprKind::SizeofTy(SizeofTyExpr { ty }),
+                        cursor.span_from(start),
+                    ))
+                }
+                None => munch_unary_expr(cursor)?,
+            };
+            return Ok(Expr::new(
+                ExprKind::Sizeof(SizeofExpr {
+                    expr: Box::new(expr),
+                }),
+                cursor.span_from(start),
+            ));
+        }
+        Some(Token::Kw(Kw::Alignof)) => {
+            cursor.advance(1);
+            munch_punct(cursor, Punct::LParen)?;
+            let ty = munch_ty_name(cursor)?;
+            munch_punct(cursor, Punct::RParen)?;
+            return Ok(Expr::new(
+                ExprKind::Alignof(AlignofExpr { ty }),
+                cursor.span_from(start),
+            ));
+        }
+        Some(Token::Punct(Punct::Amp2)) => {
+            cursor.require_gnu("label addresses are a GNU extension")?;
+            cursor.advance(1);
+            let label = munch_ident(cursor)?;
+            return Ok(Expr::new(
+                ExprKind::LabelAddr(LabelAddrExpr { label }),
+                cursor.span_from(start),
+            ));
+        }
+        Some(Token::Kw(Kw::Extension)) => {
+            cursor.require_gnu("`__extension__` is a GNU extension")?;
+            cursor.advance(1);
+            let expr = munch_cast_expr(cursor)?;
+            return Ok(Expr::new(
+                ExprKind::Extension(ExtensionExpr {
+                    expr: Box::new(expr),
+                }),
+                cursor.span_from(start),
+            ));
+        }
+        _ => return munch_postfix_expr(cursor),
+    };
+    cursor.advance(1);
+    // Increment and decrement apply to a unary expression, the rest to a cast expression
+    let expr = match op {
+        UnOp::PreIncr | UnOp::PreDecr => munch_unary_expr(cursor)?,
+        _ => munch_cast_expr(cursor)?,
+    };
+    Ok(Expr::new(
+        ExprKind::UnOp(UnOpExpr {
+            op,
+            expr: Box::new(expr),
+        }),
+        cursor.span_from(start),
+    ))
+}
+
+/// Parse a cast expression, e.g. `(char *)p`
+fn munch_cast_expr(cursor: &mut TokenCursor<'_>) -> Result<Expr> {
+    let start = cursor.idx;
+    if let Some(ty) = munch_paren_ty_name(cursor)? {
+        // A type name followed by braces begins a compound literal instead
+        if let Some(Token::Punct(Punct::LBrace)) = cursor.peek() {
+            return munch_compound_lit(cursor, ty, start);
+        }
+        let expr = munch_cast_expr(cursor)?;
+        return Ok(Expr::new(
+            ExprKind::Cast(CastExpr {
+                ty,
+                expr: Box::new(expr),
+            }),
+            cursor.span_from(start),
+        ));
+    }
+    munch_unary_expr(cursor)
+}
+
+/// Returns the precedence of a binary operator, where a higher value binds tighter.
+/// Levels range from multiplicative operators (10) down to logical or (1).
+fn bin_op_prec(op: &BinOp) -> u8 {
+    match op {
+        BinOp::Mul | BinOp::Div | BinOp::Mod => 10,
+        BinOp::Add | BinOp::Sub => 9,
+        BinOp::Shl | BinOp::Shr => 8,
+        BinOp::Lt | BinOp::Gt | BinOp::Le | BinOp::Ge => 7,
+        BinOp::Eq | BinOp::Ne => 6,
+        BinOp::BitAnd => 5,
+        BinOp::BitXor => 4,
+        BinOp::BitOr => 3,
+        BinOp::LogAnd => 2,
+        BinOp::LogOr => 1,
+    }
+}
+
+/// Parse a chain of left-associative binary operators using precedence climbing,
+/// consuming only operators with precedence of at least `min_prec`
+fn munch_bin_op_expr(cursor: &mut TokenCursor<'_>, min_prec: u8) -> Result<Expr> {
+    let start = cursor.idx;
+    let mut left = munch_cast_expr(cursor)?;
+    while let Some(&Token::Punct(punct)) = cursor.peek() {
+        let Some(op) = BinOp::from_punct(punct) else {
+            break;
+        };
+        let prec = bin_op_prec(&op);
+        if prec < min_prec {
+            break;
+        }
+        cursor.advance(1);
+        let right = munch_bin_op_expr(cursor, prec + 1)?;
+        left = Expr::new(
+            ExprKind::BinOp(BinOpExpr {
+                left: Box::new(left),
+                right: Box::new(right),
+                op,
+            }),
+            cursor.span_from(start),
+        );
+    }
+    Ok(left)
+}
+
+/// Parse a conditional expression, which is right-associative
+/// e.g. `a ? b : c ? d : e` => `a ? b : (c ? d : e)`
+fn munch_cond_expr(cursor: &mut TokenCursor<'_>) -> Result<Expr> {
+    let start = cursor.idx;
+    let cond = munch_bin_op_expr(cursor, 1)?;
+    if let Some(Token::Punct(Punct::Question)) = cursor.peek() {
+        cursor.advance(1);
+        let expr_true = munch_expr(cursor)?;
+        munch_punct(cursor, Punct::Colon)?;
+        let expr_false = munch_cond_expr(cursor)?;
+        return Ok(Expr::new(
+            ExprKind::Cond(CondExpr {
+                cond: Box::new(cond),
+                expr_true: Box::new(expr_true),
+                expr_false: Box::new(expr_false),
+            }),
+            cursor.span_from(start),
+        ));
+    }
+    Ok(cond)
+}
+
+/// Parse an assignment expression, which is right-associative
+/// e.g. `a = b += c` => `a = (b += c)`
+fn munch_assn_expr(cursor: &mut TokenCursor<'_>) -> Result<Expr> {
+    let start = cursor.idx;
+    let lvalue = munch_cond_expr(cursor)?;
+    if let Some(&Token::Punct(punct)) = cursor.peek() {
+        if let Some(op) = AssnOp::from_punct(punct) {
+            cursor.advance(1);
+            let expr = munch_assn_expr(cursor)?;
+            return Ok(Expr::new(
+                ExprKind::Assn(AssnExpr {
+                    lvalue: Box::new(lvalue),
+                    expr: Box::new(expr),
+                    op,
+                }),
+                cursor.span_from(start),
+            ));
+        }
+    }
+    Ok(lvalue)
+}
+
+/// Parse an expression, including the comma operator
+/// e.g. `a = b + c * d[i]->f(x, y), z`
+fn munch_expr(cursor: &mut TokenCursor<'_>) -> Result<Expr> {
+    let start = cursor.idx;
+    let mut left = munch_assn_expr(cursor)?;
+    while let Some(Token::Punct(Punct::Comma)) = cursor.peek() {
+        cursor.advance(1);
+        let right = munch_assn_expr(cursor)?;
+        left = Expr::new(
+            ExprKind::Comma(CommaExpr {
+                left: Box::new(left),
+                right: Box::new(right),
+            }),
+            cursor.span_from(start),
+        );
+    }
+    Ok(left)
+}
+
+/// Parse the empty statement `;`
+fn munch_empty_stmt(cursor: &mut TokenCursor<'_>) -> Result<()> {
+    if let Some(Token::Punct(Punct::Semi)) = cursor.peek() {
+        cursor.advance(1);
+        Ok(())
+    } else {
+        bail!(cursor.expected("`;`"));
+    }
+}
+
+/// Parse a declaration of a struct, union or enum tag without any declarators
+/// e.g. `struct point { int x; int y; };`, `struct node;` or `enum { A, B };`
+fn munch_tag_decl(cursor: &mut TokenCursor<'_>) -> Result<Ty> {
+    let mut new_cursor = cursor.clone();
+    let attrs_start = new_cursor.idx;
+    let attrs = munch_decl_prefix(&mut new_cursor)?;
+    let mut ty = match new_cursor.peek() {
+        Some(Token::Kw(Kw::Enum)) => munch_enum_spec(&mut new_cursor)?,
+        _ => munch_struct_spec(&mut new_cursor)?,
+    };
+    munch_punct(&mut new_cursor, Punct::Semi)?;
+    // Leading attributes belong to the tag being defined
+    match &mut ty.kind {
+        TyKind::StructDefn(StructDefn {
+            attrs: tag_attrs, ..
+        })
+        | TyKind::EnumDefn(EnumDefn {
+            attrs: tag_attrs, ..
+        }) => {
+            tag_attrs.splice(0..0, attrs);
+        }
+        _ if !attrs.is_empty() => {
+            new_cursor.warn_at(attrs_start, "attributes ignored on a tag declaration");
+        }
+        _ => {}
+    }
+    cursor.replace(new_cursor);
+    Ok(ty)
+}
+
+/// Parse the initial value of a declared object
+/// e.g. `5`, `"hello"` or `{ 1, 2, 3 }`
+fn munch_initializer(cursor: &mut TokenCursor<'_>) -> Result<Initializer> {
+    match cursor.peek() {
+        Some(Token::Punct(Punct::LBrace)) => Ok(Initializer::List(munch_init_list(cursor)?)),
+        _ => Ok(Initializer::Expr(munch_assn_expr(cursor)?)),
+    }
+}
+
+/// Parse a brace-enclosed initializer list, where each entry may be designated
+/// e.g. `{ 1, 2, 3 }`, `{ .x = 1, .y = 2 }` or `{ [0].name = "a", [4] = { 0 } }`
+fn munch_init_list(cursor: &mut TokenCursor<'_>) -> Result<InitList> {
+    munch_punct(cursor, Punct::LBrace)?;
+    let mut items = Vec::new();
+    loop {
+        if let Some(Token::Punct(Punct::RBrace)) = cursor.peek() {
+            cursor.advance(1);
+            break;
+        }
+        let mut designators = Vec::new();
+        loop {
+            match cursor.peek() {
+                Some(Token::Punct(Punct::Dot)) => {
+                    cursor.advance(1);
+                    designators.push(Designator::Field(munch_ident(cursor)?));
+                }
+                Some(Token::Punct(Punct::LBrack)) => {
+                    cursor.advance(1);
+                    designators.push(Designator::Index(munch_cond_expr(cursor)?));
+                    munch_punct(cursor, Punct::RBrack)?;
+                }
+                _ => break,
+            }
+        }
+        if !designators.is_empty() {
+            munch_punct(cursor, Punct::Eq)?;
+        }
+        let init = munch_initializer(cursor)?;
+        items.push(InitItem { designators, init });
+        match cursor.peek() {
+            Some(Token::Punct(Punct::Comma)) => cursor.advance(1),
+            Some(Token::Punct(Punct::RBrace)) => {
+                cursor.advance(1);
+                break;
+            }
+            _ => bail!(cursor.expected("`,` or `}`")),
+        }
+    }
+    Ok(InitList { items })
+}
+
+/// Parse a declaration, which may declare several variables, functions or typedefs
+/// sharing the same specifiers
+/// e.g. `static const char *a = "a", b[10];`, `int f(int), g(void);` or `typedef int T;`
+fn munch_decl(cursor: &mut TokenCursor<'_>) -> Result<Vec<Stmt>> {
+    let decl_start = cursor.idx;
+    let specs = munch_decl_specs(cursor)?;
+    let mut stmts = Vec::new();
+    loop {
+        let start = cursor.idx;
+        let (ty, ident, attrs) = munch_typed_ident(cursor, specs.ty.clone(), DeclaratorCtx::Other)?;
+        let asm_label = match cursor.peek() {
+            Some(Token::Kw(Kw::Asm)) => Some(munch_asm_label(cursor)?),
+            _ => None,
+        };
+        // Attributes may also follow an asm label
+        let attrs = [specs.attrs.clone(), attrs, munch_attrs(cursor)?].concat();
+        // The name is in scope from the end of its declarator, including the initializer
+        let is_typedef = specs.storage == Some(StorageClass::Typedef);
+        cursor.declare(&ident, is_typedef);
+        let init = match cursor.peek() {
+            Some(Token::Punct(Punct::Eq)) => {
+                cursor.advance(1);
+                Some(munch_initializer(cursor)?)
+            }
+            _ => None,
+        };
+        let has_align = !specs.align.is_empty();
+        let kind = match (specs.storage, ty.kind) {
+            (Some(StorageClass::Typedef), _) if init.is_some() => {
+                bail!(cursor.error_at(start, "typedef cannot be initialized"))
+            }
+            (Some(StorageClass::Typedef), _) if has_align => {
+                bail!(cursor.error_at(start, "`_Alignas` cannot be used on a typedef"))
+            }
+            (Some(StorageClass::Typedef), _) if asm_label.is_some() => {
+                bail!(cursor.error_at(start, "`__asm__` label cannot be used on a typedef"))
+            }
+            (Some(StorageClass::Typedef), kind) => StmtKind::Typedef(TypedefDecl {
+                attrs,
+                ty: Ty::new(kind, ty.span),
+                ident,
+            }),
+            (_, TyKind::Fn(_)) if init.is_some() => {
+                bail!(cursor.error_at(start, "function cannot be initialized"))
+            }
+            (_, TyKind::Fn(_)) if has_align => {
+                bail!(cursor.error_at(start, "`_Alignas` cannot be used on a function"))
+            }
+            (Some(storage @ (StorageClass::Auto | StorageClass::Register)), TyKind::Fn(_)) => {
+                bail!(cursor.error_at(
+                    start,
+                    format!("function cannot be `{}`", storage.to_kw().to_str())
+                ))
+            }
+            (storage, TyKind::Fn(fn_ty)) => StmtKind::FnDecl(FnDecl {
+                attrs,
+                storage,
+                is_inline: specs.is_inline,
+                name: ident,
+                asm_label,
+                ty: fn_ty,
+            }),
+            (_, _) if specs.is_inline => {
+                bail!(cursor.error_at(start, "`inline` can only appear on functions"))
+            }
+            (Some(StorageClass::Register), _) if has_align => {
+                bail!(cursor.error_at(start, "`_Alignas` cannot be combined with `register`"))
+            }
+            (storage, kind) => StmtKind::Decl(DeclStmt {
+                attrs,
+                storage,
+                align: specs.align.clone(),
+                ty: Ty::new(kind, ty.span),
+                ident,
+                asm_label,
+                init,
+            }),
+        };
+        stmts.push(Stmt::new(kind, cursor.span_from(decl_start)));
+        match cursor.peek() {
+            Some(Token::Punct(Punct::Comma)) => cursor.advance(1),
+            Some(Token::Punct(Punct::Semi)) => {
+                cursor.advance(1);
+                break;
+            }
+            _ => bail!(cursor.expected("`,` or `;`")),
+        }
+    }
+    Ok(stmts)
+}
+
+/// Parse a GNU asm label, which gives the symbol name used for a declaration
+/// e.g. `__asm__("__isoc99_scanf")`
+fn munch_asm_label(cursor: &mut TokenCursor<'_>) -> Result<String> {
+    match cursor.peek() {
+        Some(Token::Kw(Kw::Asm)) => {
+            cursor.require_gnu("`__asm__` labels are a GNU extension")?;
+            cursor.advance(1);
+        }
+        _ => bail!(cursor.expected("`__asm__`")),
+    }
+    munch_punct(cursor, Punct::LParen)?;
+    let label = match cursor.peek() {
+        Some(Token::Lit(Lit::Str(lit))) => lit.clone(),
+        _ => bail!(cursor.expected("string literal")),
+    };
+    cursor.advance(1);
+    munch_punct(cursor, Punct::RParen)?;
+    Ok(label)
+}
+
+/// Parse a static assertion, whose message may be left out since C23
+/// e.g. `_Static_assert(sizeof(int) == 4, "int must be 32 bits");` or `_Static_assert(N > 0);`
+fn munch_static_assert(cursor: &mut TokenCursor<'_>) -> Result<StaticAssert> {
+    match cursor.peek() {
+        Some(Token::Kw(Kw::StaticAssert)) => cursor.advance(1),
+        _ => bail!(cursor.expected("`_Static_assert`")),
+    }
+    munch_punct(cursor, Punct::LParen)?;
+    let cond = munch_cond_expr(cursor)?;
+    let mut message = None;
+    if let Some(Token::Punct(Punct::Comma)) = cursor.peek() {
+        cursor.advance(1);
+        match cursor.peek() {
+            Some(Token::Lit(Lit::Str(lit))) => {
+                message = Some(lit.clone());
+                cursor.advance(1);
+            }
+            _ => bail!(cursor.expected("string literal")),
+        }
+    }
+    munch_punct(cursor, Punct::RParen)?;
+    munch_punct(cursor, Punct::Semi)?;
+    Ok(StaticAssert { cond, message })
+}
+
+/// Parse an expression statement
+/// e.g. `printf("Hello, world!");`
+fn munch_expr_stmt(cursor: &mut TokenCursor<'_>) -> Result<ExprStmt> {
+    let expr = munch_expr(cursor)?;
+    munch_punct(cursor, Punct::Semi)?;
+    Ok(ExprStmt { expr })
+}
+
+/// Parse a return statement
+/// e.g. `return 0;`
+fn munch_return_stmt(cursor: &mut TokenCursor<'_>) -> Result<ReturnStmt> {
+    match cursor.peek() {
+        Some(Token::Kw(Kw::Return)) => cursor.advance(1),
+        _ => bail!(cursor.expected("`return`")),
+    }
+    if let Some(Token::Punct(Punct::Semi)) = cursor.peek() {
+        cursor.advance(1);
+        return Ok(ReturnStmt { expr: None });
+    }
+    let expr = munch_expr(cursor)?;
+    munch_punct(cursor, Punct::Semi)?;
+    Ok(ReturnStmt { expr: Some(expr) })
+}
+
+/// Parse an if statement, with or without an else branch. A dangling `else`
+/// binds to the nearest `if`, e.g. `if (a) if (b) x; else y;` => `if (a) { if (b) x; else y; }`
+fn munch_if_stmt(cursor: &mut TokenCursor<'_>) -> Result<StmtKind> {
+    match cursor.peek() {
+        Some(Token::Kw(Kw::If)) => cursor.advance(1),
+        _ => bail!(cursor.expected("`if`")),
+    }
+    munch_punct(cursor, Punct::LParen)?;
+    let cond = munch_expr(cursor)?;
+    munch_punct(cursor, Punct::RParen)?;
+    let stmt = munch_stmt(cursor)?;
+    if let Some(Token::Kw(Kw::Else)) = cursor.peek() {
+        cursor.advance(1);
+        let stmt_false = munch_stmt(cursor)?;
+        return Ok(StmtKind::IfElse(IfElseStmt {
+            cond,
+            stmt_true: Box::new(stmt),
+            stmt_false: Box::new(stmt_false),
+        }));
+    }
+    Ok(StmtKind::If(IfStmt {
+        cond,
+        stmt: Box::new(stmt),
+    }))
+}
+
+/// Parse a while loop
+/// e.g. `while (i < n) i++;`
+fn munch_while_stmt(cursor: &mut TokenCursor<'_>) -> Result<WhileStmt> {
+    match cursor.peek() {
+        Some(Token::Kw(Kw::While)) => cursor.advance(1),
+        _ => bail!(cursor.expected("`while`")),
     }
+    munch_punct(cursor, Punct::LParen)?;
+    let cond = munch_expr(cursor)?;
+    munch_punct(cursor, Punct::RParen)?;
+    let stmt = munch_stmt(cursor)?;
+    Ok(WhileStmt {
+        cond,
+        stmt: Box::new(stmt),
+    })
+}
 
-    /// Return the next unconsumed token and advance the cursor by 1
-    pub fn next(&mut self) -> Option<&Token> {
-        let token = self.tokens.get(self.idx);
-        if self.idx < self.tokens.len() {
-            self.idx += 1;
-        }
-        token
+/// Parse a do-while loop
+/// e.g. `do i++; while (i < n);`
+fn munch_do_while_stmt(cursor: &mut TokenCursor<'_>) -> Result<DoWhileStmt> {
+    match cursor.peek() {
+        Some(Token::Kw(Kw::Do)) => cursor.advance(1),
+        _ => bail!(cursor.expected("`do`")),
+    }
+    let stmt = munch_stmt(cursor)?;
+    match cursor.peek() {
+        Some(Token::Kw(Kw::While)) => cursor.advance(1),
+        _ => bail!(cursor.expected("`while`")),
     }
+    munch_punct(cursor, Punct::LParen)?;
+    let cond = munch_expr(cursor)?;
+    munch_punct(cursor, Punct::RParen)?;
+    munch_punct(cursor, Punct::Semi)?;
+    Ok(DoWhileStmt {
+        stmt: Box::new(stmt),
+        cond,
+    })
+}
 
-    /// Advance the cursor by 1. Will panic if advanced past the end of the stream, so
-    /// you must ensure that there are tokens to consume. This function is usually paired
-    /// with `TokenCursor::peek` or `TokenCursor::npeek`.
-    pub fn advance(&mut self, amount: usize) {
-        if self.idx + amount > self.tokens.len() {
-            panic!("advanced past end of cursor");
+/// Parse a for loop, where each clause may be omitted
+/// e.g. `for (int i; i < n; i++) {}` or `for (;;) {}`
+fn munch_for_stmt(cursor: &mut TokenCursor<'_>) -> Result<ForStmt> {
+    match cursor.peek() {
+        Some(Token::Kw(Kw::For)) => cursor.advance(1),
+        _ => bail!(cursor.expected("`for`")),
+    }
+    munch_punct(cursor, Punct::LParen)?;
+    // A declaration in the first clause is only visible within the loop
+    cursor.push_scope();
+    let init = if is_decl_start(cursor) {
+        // The declaration consumes the trailing `;`
+        let start = cursor.idx;
+        let mut decls = Vec::new();
+        for stmt in munch_decl(cursor)? {
+            match stmt.kind {
+                StmtKind::Decl(
+                    decl @ DeclStmt {
+                        storage: None | Some(StorageClass::Auto | StorageClass::Register),
+                        ..
+                    },
+                ) => decls.push(decl),
+                _ => {
+                    bail!(cursor.error_at(
+                        start,
+                        "for loop can only declare `auto` or `register` variables"
+                    ))
+                }
+            }
         }
-        self.idx += amount;
+        Some(ForInit::Decl(decls))
+    } else if let Ok(()) = munch_empty_stmt(cursor) {
+        None
+    } else {
+        let expr = munch_expr(cursor)?;
+        munch_punct(cursor, Punct::Semi)?;
+        Some(ForInit::Expr(expr))
+    };
+    let cond = match cursor.peek() {
+        Some(Token::Punct(Punct::Semi)) => None,
+        _ => Some(munch_expr(cursor)?),
+    };
+    munch_punct(cursor, Punct::Semi)?;
+    let step = match cursor.peek() {
+        Some(Token::Punct(Punct::RParen)) => None,
+        _ => Some(munch_expr(cursor)?),
+    };
+    munch_punct(cursor, Punct::RParen)?;
+    let stmt = munch_stmt(cursor)?;
+    cursor.pop_scope();
+    Ok(ForStmt {
+        init,
+        cond,
+        step,
+        stmt: Box::new(stmt),
+    })
+}
+
+/// Parse a switch statement
+/// e.g. `switch (x) { case 1: break; default: break; }`
+fn munch_switch_stmt(cursor: &mut TokenCursor<'_>) -> Result<SwitchStmt> {
+    match cursor.peek() {
+        Some(Token::Kw(Kw::Switch)) => cursor.advance(1),
+        _ => bail!(cursor.expected("`switch`")),
     }
+    munch_punct(cursor, Punct::LParen)?;
+    let cond = munch_expr(cursor)?;
+    munch_punct(cursor, Punct::RParen)?;
+    let stmt = munch_stmt(cursor)?;
+    Ok(SwitchStmt {
+        cond,
+        stmt: Box::new(stmt),
+    })
+}
 
-    /// Replace the current cursor with another cursor, usually used to commit a "hypothetical" cursor
-    pub fn replace(&mut self, cursor: TokenCursor<'a>) {
-        let _ = std::mem::replace(self, cursor);
+/// Parse a case label and the statement it labels, including GNU case ranges
+/// e.g. `case 1: x++;` or `case 'a' ... 'z': x++;`
+fn munch_case_stmt(cursor: &mut TokenCursor<'_>) -> Result<CaseStmt> {
+    match cursor.peek() {
+        Some(Token::Kw(Kw::Case)) => cursor.advance(1),
+        _ => bail!(cursor.expected("`case`")),
+    }
+    let expr = munch_cond_expr(cursor)?;
+    let mut expr_end = None;
+    if let Some(Token::Punct(Punct::Ellipsis)) = cursor.peek() {
+        cursor.require_gnu("case ranges are a GNU extension")?;
+        cursor.advance(1);
+        expr_end = Some(munch_cond_expr(cursor)?);
     }
+    munch_punct(cursor, Punct::Colon)?;
+    let stmt = munch_stmt(cursor)?;
+    Ok(CaseStmt {
+        expr,
+        expr_end,
+        stmt: Box::new(stmt),
+    })
 }
 
-/// Parse a typed identifier, returning both the type and the identifier portion.
-/// Typed identifiers seem to be a common pattern throughout C, used in
-/// variable declarations, parameter declarations, etc.
-///
-/// e.g. `struct my_struct ptr[10]` => length 10 array of struct my_struct
-fn munch_typed_ident<'a>(cursor: &mut TokenCursor<'a>) -> Result<(Ty, String)> {
-    let mut new_cursor = cursor.clone();
+/// Parse a default label and the statement it labels
+/// e.g. `default: return 0;`
+fn munch_default_stmt(cursor: &mut TokenCursor<'_>) -> Result<DefaultStmt> {
+    match cursor.peek() {
+        Some(Token::Kw(Kw::Default)) => cursor.advance(1),
+        _ => bail!(cursor.expected("`default`")),
+    }
+    munch_punct(cursor, Punct::Colon)?;
+    let stmt = munch_stmt(cursor)?;
+    Ok(DefaultStmt {
+        stmt: Box::new(stmt),
+    })
+}
 
-    let mut ty: Option<Ty>;
-    let ident: Option<String>;
+/// Parse a labeled statement
+/// e.g. `retry: x++;`
+fn munch_label_stmt(cursor: &mut TokenCursor<'_>) -> Result<LabelStmt> {
+    let label = munch_ident(cursor)?;
+    munch_punct(cursor, Punct::Colon)?;
+    let stmt = munch_stmt(cursor)?;
+    Ok(LabelStmt {
+        label,
+        stmt: Box::new(stmt),
+    })
+}
 
-    // Munch type prefix
-    match new_cursor.npeek() {
-        &[Token::Kw(Kw::Void), ..] => {
-            ty = Some(Ty::Basic(BasicTy::Void));
-            new_cursor.advance(1);
-        }
-        &[Token::Kw(Kw::Int), ..] => {
-            ty = Some(Ty::Basic(BasicTy::Int));
-            new_cursor.advance(1);
-        }
-        &[Token::Kw(Kw::Unsigned), Token::Kw(Kw::Int), ..] => {
-            ty = Some(Ty::Basic(BasicTy::UnsignedInt));
-            new_cursor.advance(2);
-        }
-        &[Token::Kw(Kw::Char), ..] => {
-            ty = Some(Ty::Basic(BasicTy::Char));
-            new_cursor.advance(1);
-        }
-        &[Token::Kw(Kw::Struct), Token::Ident(ref id), ..] => {
-            ty = Some(Ty::Struct(StructTy { name: id.clone() }));
-            new_cursor.advance(2);
-        }
-        _ => bail!("expected type"),
+/// Parse a goto statement, including GNU computed gotos
+/// e.g. `goto retry;` or `goto *targets[i];`
+fn munch_goto_stmt(cursor: &mut TokenCursor<'_>) -> Result<StmtKind> {
+    match cursor.peek() {
+        Some(Token::Kw(Kw::Goto)) => cursor.advance(1),
+        _ => bail!(cursor.expected("`goto`")),
+    }
+    if let Some(Token::Punct(Punct::Star)) = cursor.peek() {
+        cursor.require_gnu("computed `goto` is a GNU extension")?;
+        cursor.advance(1);
+        let expr = munch_expr(cursor)?;
+        munch_punct(cursor, Punct::Semi)?;
+        return Ok(StmtKind::ComputedGoto(ComputedGotoStmt { expr }));
     }
+    let label = munch_ident(cursor)?;
+    munch_punct(cursor, Punct::Semi)?;
+    Ok(StmtKind::Goto(GotoStmt { label }))
+}
 
-    // Munch pointer
+/// Parse a brace-enclosed sequence of block items, as used for blocks and function bodies
+/// e.g. `{ int a; a = 1; }`
+fn munch_block_items(cursor: &mut TokenCursor<'_>) -> Result<Vec<Stmt>> {
+    munch_punct(cursor, Punct::LBrace)?;
+    cursor.push_scope();
+    let mut stmts = Vec::new();
     loop {
-        match new_cursor.peek() {
-            Some(&Token::Punct(Punct::Star)) => {
-                ty = Some(Ty::Ptr(PtrTy {
-                    ty: Box::new(ty.unwrap()),
-                }));
-                new_cursor.advance(1);
-            }
-            _ => break,
+        if let Some(Token::Punct(Punct::RBrace)) = cursor.peek() {
+            cursor.advance(1);
+            cursor.pop_scope();
+            break;
         }
-    }
-
-    // Munch identifier
-    match new_cursor.peek() {
-        Some(&Token::Ident(ref id)) => {
-            ident = Some(id.clone());
-            new_cursor.advance(1);
+        // Keep what was parsed if the closing brace is missing
+        if cursor.is_empty() {
+            let err = cursor.expected("`}`");
+            cursor.report(err);
+            cursor.pop_scope();
+            break;
         }
-        _ => bail!("expected identifier"),
-    }
-
-    // Munch array brackets
-    match new_cursor.peek() {
-        Some(Token::Punct(Punct::LBrack)) => {
-            new_cursor.advance(1);
-            match new_cursor.npeek() {
-                &[Token::Lit(Lit::Int(num)), Token::Punct(Punct::RBrack), ..] => {
-                    let inner_ty = ty.unwrap();
-                    if let Ty::Basic(BasicTy::Void) = inner_ty {
-                        bail!("cannot have array of void");
-                    }
-                    ty = Some(Ty::Array(ArrayTy {
-                        ty: Box::new(inner_ty),
-                        length: num,
-                    }));
-                    new_cursor.advance(2);
-                }
-                &[Token::Punct(Punct::RBrack), ..] => {
-                    bail!("array with no type currently not supported");
+        let start = cursor.idx;
+        let scopes = Rc::clone(&cursor.scopes);
+        match munch_block_item(cursor) {
+            Ok(mut items) => stmts.append(&mut items),
+            Err(err) => {
+                // Close any scopes the failed block item left open
+                cursor.scopes = scopes;
+                // Functions cannot be nested, so a definition that fails as a block item
+                // must follow the missing closing brace
+                let end = cursor.idx;
+                cursor.idx = start;
+                if is_fn_defn(cursor) {
+                    let err = cursor.expected("`}`");
+                    cursor.report(err);
+                    cursor.pop_scope();
+                    break;
                 }
-                _ => {}
+                cursor.idx = end;
+                cursor.recover(err, start);
             }
         }
-        _ => {}
-    }
-
-    cursor.replace(new_cursor);
-    match (ty, ident) {
-        (Some(ty), Some(ident)) => Ok((ty, ident)),
-        _ => unreachable!(),
     }
+    Ok(stmts)
 }
 
-/// Parse the empty statement `;`
-fn munch_empty_stmt(cursor: &mut TokenCursor<'_>) -> Result<()> {
-    if let Some(Token::Punct(Punct::Semi)) = cursor.peek() {
-        cursor.advance(1);
-        Ok(())
-    } else {
-        bail!("expected `;`");
+/// Parse a block item, which is either a declaration or a statement. A single
+/// declaration may expand to several statements, e.g. `int a, b;`
+fn munch_block_item(cursor: &mut TokenCursor<'_>) -> Result<Vec<Stmt>> {
+    let start = cursor.idx;
+    let kind = match munch_tag_decl(cursor).map(|ty| ty.kind) {
+        Ok(TyKind::StructDefn(struct_defn)) => Some(StmtKind::StructDefn(struct_defn)),
+        Ok(TyKind::Struct(struct_ty)) => Some(StmtKind::StructDecl(struct_ty)),
+        Ok(TyKind::EnumDefn(enum_defn)) => Some(StmtKind::EnumDefn(enum_defn)),
+        _ => None,
+    };
+    if let Some(kind) = kind {
+        return Ok(vec![Stmt::new(kind, cursor.span_from(start))]);
+    }
+    if let Some(Token::Kw(Kw::StaticAssert)) = cursor.peek() {
+        let kind = StmtKind::StaticAssert(munch_static_assert(cursor)?);
+        return Ok(vec![Stmt::new(kind, cursor.span_from(start))]);
     }
+    // Labels live in their own namespace, so `T:` is a label even if `T` names a type
+    if let [Token::Ident(_), Token::Punct(Punct::Colon), ..] = cursor.npeek() {
+        return Ok(vec![munch_stmt(cursor)?]);
+    }
+    // Anything that begins with a type must be a declaration
+    if is_decl_start(cursor) {
+        return munch_decl(cursor);
+    }
+    Ok(vec![munch_stmt(cursor)?])
 }
 
 /// Parse a statement
 fn munch_stmt(cursor: &mut TokenCursor<'_>) -> Result<Stmt> {
-    // Try to parse empty statement
-    if let Ok(()) = munch_empty_stmt(cursor) {
-        return Ok(Stmt::Empty);
-    }
-    bail!("not implemented")
+    let start = cursor.idx;
+    let attrs = munch_attrs(cursor)?;
+    let kind = match cursor.peek() {
+        Some(Token::Punct(Punct::Semi)) => {
+            cursor.advance(1);
+            StmtKind::Empty
+        }
+        Some(Token::Punct(Punct::LBrace)) => {
+            let stmts = munch_block_items(cursor)?;
+            StmtKind::Block(BlockStmt { stmts })
+        }
+        Some(Token::Kw(Kw::If)) => munch_if_stmt(cursor)?,
+        Some(Token::Kw(Kw::While)) => StmtKind::While(munch_while_stmt(cursor)?),
+        Some(Token::Kw(Kw::Do)) => StmtKind::DoWhile(munch_do_while_stmt(cursor)?),
+        Some(Token::Kw(Kw::For)) => StmtKind::For(munch_for_stmt(cursor)?),
+        Some(Token::Kw(Kw::Switch)) => StmtKind::Switch(munch_switch_stmt(cursor)?),
+        Some(Token::Kw(Kw::Case)) => StmtKind::Case(munch_case_stmt(cursor)?),
+        Some(Token::Kw(Kw::Default)) => StmtKind::Default(munch_default_stmt(cursor)?),
+        Some(Token::Kw(Kw::Goto)) => munch_goto_stmt(cursor)?,
+        Some(Token::Ident(_))
+            if matches!(cursor.npeek().get(1), Some(Token::Punct(Punct::Colon))) =>
+        {
+            StmtKind::Label(munch_label_stmt(cursor)?)
+        }
+        Some(Token::Kw(Kw::Break)) => {
+            cursor.advance(1);
+            munch_punct(cursor, Punct::Semi)?;
+            StmtKind::Break
+        }
+        Some(Token::Kw(Kw::Continue)) => {
+            cursor.advance(1);
+            munch_punct(cursor, Punct::Semi)?;
+            StmtKind::Continue
+        }
+        Some(Token::Kw(Kw::Return)) => StmtKind::Return(munch_return_stmt(cursor)?),
+        _ => StmtKind::Expr(munch_expr_stmt(cursor)?),
+    };
+    let mut stmt = Stmt::new(kind, cursor.span_from(start));
+    stmt.attrs = attrs;
+    Ok(stmt)
 }
 
 /// Parse a function definition
 /// e.g. `int main() { /* */ }`
 fn munch_fn_defn(cursor: &mut TokenCursor<'_>) -> Result<FnDefn> {
     let mut new_cursor = cursor.clone();
-    let (ret, name) = munch_typed_ident(&mut new_cursor).context("expected typed identifier")?;
-    match new_cursor.next() {
-        Some(Token::Punct(Punct::LParen)) => {}
-        _ => bail!("expected `(`"),
+    let specs = munch_decl_specs(&mut new_cursor)?;
+    let (ty, name, attrs) = munch_typed_ident(&mut new_cursor, specs.ty, DeclaratorCtx::FnDefn)?;
+    let TyKind::Fn(FnTy {
+        ret,
+        mut params,
+        variadic,
+        style,
+    }) = ty.kind
+    else {
+        bail!(new_cursor.expected("`(`"));
+    };
+    if let Some(storage @ (StorageClass::Typedef | StorageClass::Auto | StorageClass::Register)) =
+        specs.storage
+    {
+        bail!(new_cursor.error(format!("function cannot be `{}`", storage.to_kw().to_str())));
     }
-    let mut params = Vec::new();
-    // Special case: void parameter list
-    if let &[Token::Kw(Kw::Void), Token::Punct(Punct::RParen), ..] = new_cursor.npeek() {
-        new_cursor.advance(2);
-    } else {
-        loop {
-            if let Ok((ty, ident)) = munch_typed_ident(&mut new_cursor) {
-                let param = Param { ident, ty };
-                params.push(param);
-                match new_cursor.next() {
-                    Some(Token::Punct(Punct::Comma)) => continue,
-                    Some(Token::Punct(Punct::RParen)) => break,
-                    _ => bail!("expected `,` or `)`"),
-                }
-            }
-            if let Some(Token::Punct(Punct::RParen)) = new_cursor.peek() {
-                new_cursor.advance(1);
-                break;
-            }
-            bail!("expected parameter or `)`");
-        }
+    if !specs.align.is_empty() {
+        bail!(new_cursor.error("`_Alignas` cannot be used on a function"));
     }
-    match new_cursor.next() {
-        Some(Token::Punct(Punct::LBrace)) => {}
-        _ => bail!("expected `{{`"),
+    if style == ParamStyle::OldStyle {
+        munch_param_decls(&mut new_cursor, &mut params)?;
     }
-    let mut body = Vec::new();
-    loop {
-        if let Ok(stmt) = munch_stmt(&mut new_cursor) {
-            body.push(stmt);
-            continue;
-        }
-        if let Some(Token::Punct(Punct::RBrace)) = new_cursor.peek() {
-            new_cursor.advance(1);
-            break;
+    // Commit to the definition before the body, so errors recovered from inside it are
+    // kept even if the body fails as a whole
+    cursor.replace(new_cursor);
+    cursor.declare(&name, false);
+    // Parameters are scoped to the function body
+    cursor.push_scope();
+    for param in &params {
+        if let Some(ident) = &param.ident {
+            cursor.declare(ident, false);
         }
-        bail!("expected `}}`");
     }
+    let body = munch_block_items(cursor)?;
+    cursor.pop_scope();
 
-    cursor.replace(new_cursor);
     Ok(FnDefn {
+        attrs: [specs.attrs, attrs].concat(),
+        storage: specs.storage,
+        is_inline: specs.is_inline,
         name,
-        ret,
+        ret: *ret,
         params,
+        variadic,
+        style,
         stmts: body,
     })
 }
 
+/// Parse the declarations of an old-style definition's parameters, which come between
+/// the declarator and the body, and fill in the parameter types
+/// e.g. `int a; char *b, **c;`
+fn munch_param_decls(cursor: &mut TokenCursor<'_>, params: &mut [Param]) -> Result<()> {
+    let mut declared = vec![false; params.len()];
+    while !matches!(cursor.peek(), Some(Token::Punct(Punct::LBrace))) {
+        if !is_decl_start(cursor) {
+            bail!(cursor.expected("parameter declaration or `{`"));
+        }
+        let decl_start = cursor.idx;
+        let specs = munch_decl_specs(cursor)?;
+        match specs.storage {
+            None | Some(StorageClass::Register) => {}
+            Some(storage) => bail!(cursor.error_at(
+                decl_start,
+                format!("parameter cannot be `{}`", storage.to_kw().to_str())
+            )),
+        }
+        if specs.is_inline {
+            bail!(cursor.error_at(decl_start, "`inline` can only appear on functions"));
+        }
+        if !specs.align.is_empty() {
+            bail!(cursor.error_at(decl_start, "`_Alignas` cannot be used on a parameter"));
+        }
+        loop {
+            let start = cursor.idx;
+            let (ty, ident, attrs) =
+                munch_typed_ident(cursor, specs.ty.clone(), DeclaratorCtx::Param)?;
+            let Some(idx) = params
+                .iter()
+                .position(|param| param.ident.as_ref() == Some(&ident))
+            else {
+                bail!(cursor.error_at(
+                    start,
+                    format!("declaration of `{ident}` does not match any parameter")
+                ));
+            };
+            if declared[idx] {
+                bail!(cursor.error_at(
+                    start,
+                    format!("duplicate declaration of parameter `{ident}`")
+                ));
+            }
+            declared[idx] = true;
+            params[idx].attrs = [specs.attrs.clone(), attrs].concat();
+            params[idx].ty = ty;
+            params[idx].span = cursor.span_from(decl_start);
+            match cursor.peek() {
+                Some(Token::Punct(Punct::Comma)) => cursor.advance(1),
+                Some(Token::Punct(Punct::Semi)) => {
+                    cursor.advance(1);
+                    break;
+                }
+                _ => bail!(cursor.expected("`,` or `;`")),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether the next tokens begin a function definition, which is a
+/// declarator of function type followed by the function body, or by the parameter
+/// declarations of an old-style definition
+fn is_fn_defn(cursor: &TokenCursor<'_>) -> bool {
+    let mut new_cursor = cursor.clone();
+    let Ok(specs) = munch_decl_specs(&mut new_cursor) else {
+        return false;
+    };
+    let Ok((
+        Ty {
+            kind: TyKind::Fn(fn_ty),
+            ..
+        },
+        _,
+        _,
+    )) = munch_typed_ident(&mut new_cursor, specs.ty, DeclaratorCtx::FnDefn)
+    else {
+        return false;
+    };
+    match new_cursor.peek() {
+        Some(Token::Punct(Punct::LBrace)) => true,
+        _ => fn_ty.style == ParamStyle::OldStyle && is_decl_start(&new_cursor),
+    }
+}
+
+/// Parse a top-level item. A single declaration may expand to several items,
+/// e.g. `int a, b;`
+fn munch_item(cursor: &mut TokenCursor<'_>) -> Result<Vec<Item>> {
+    let start = cursor.idx;
+    if is_fn_defn(cursor) {
+        let kind = ItemKind::FnDefn(munch_fn_defn(cursor)?);
+        return Ok(vec![Item::new(kind, cursor.span_from(start))]);
+    }
+    let kind = match munch_tag_decl(cursor).map(|ty| ty.kind) {
+        Ok(TyKind::StructDefn(struct_defn)) => Some(ItemKind::StructDefn(struct_defn)),
+        Ok(TyKind::Struct(struct_ty)) => Some(ItemKind::StructDecl(struct_ty)),
+        Ok(TyKind::EnumDefn(enum_defn)) => Some(ItemKind::EnumDefn(enum_defn)),
+        _ => None,
+    };
+    if let Some(kind) = kind {
+        return Ok(vec![Item::new(kind, cursor.span_from(start))]);
+    }
+    if let Some(Token::Kw(Kw::StaticAssert)) = cursor.peek() {
+        let kind = ItemKind::StaticAssert(munch_static_assert(cursor)?);
+        return Ok(vec![Item::new(kind, cursor.span_from(start))]);
+    }
+    if !is_decl_start(cursor) {
+        bail!(cursor.expected("declaration"));
+    }
+    let mut items = Vec::new();
+    for stmt in munch_decl(cursor)? {
+        let kind = match stmt.kind {
+            StmtKind::Typedef(typedef) => ItemKind::Typedef(typedef),
+            StmtKind::FnDecl(fn_decl) => ItemKind::FnDecl(fn_decl),
+            StmtKind::Decl(DeclStmt {
+                storage: Some(storage @ (StorageClass::Auto | StorageClass::Register)),
+                ..
+            }) => bail!(cursor.error_at(
+                start,
+                format!(
+                    "file-scope variable cannot be `{}`",
+                    storage.to_kw().to_str()
+                )
+            )),
+            StmtKind::Decl(DeclStmt {
+                attrs,
+                storage,
+                align,
+                ty,
+                ident,
+                asm_label,
+                init,
+            }) => ItemKind::GlobalVar(GlobalVar {
+                attrs,
+                storage,
+                align,
+                ty,
+                ident,
+                asm_label,
+                init,
+            }),
+            _ => unreachable!("declarations only produce declaration statements"),
+        };
+        items.push(Item::new(kind, stmt.span));
+    }
+    Ok(items)
+}
+
 /// Parse the token sequence into an AST tree for a C program.
 pub fn parse(tokens: Vec<Token>) -> Result<Program> {
-    let mut cursor = TokenCursor::new(&tokens);
+    parse_with_options(tokens, ParseOptions::default())
+}
+
+/// Parse the token sequence into an AST tree for a C program, accepting
+/// the dialect described by `options`. Fails with the first error encountered.
+pub fn parse_with_options(tokens: Vec<Token>, options: ParseOptions) -> Result<Program> {
+    let output = parse_with_spans(tokens, Vec::new(), options);
+    match output.errors.into_iter().next() {
+        Some(err) => Err(err.into()),
+        None => Ok(output.program),
+    }
+}
+
+/// Parse the token sequence into an AST tree for a C program, recovering from
+/// errors so that all of them are reported. Errors are located using the span
+/// of each token, as returned by `lex_with_spans`.
+pub fn parse_with_spans(
+    tokens: Vec<Token>,
+    spans: Vec<Span>,
+    options: ParseOptions,
+) -> ParseOutput {
+    let tokens = unreserve_typeof(tokens, &options);
+    let mut cursor = TokenCursor::with_options(&tokens, &spans, options);
     let mut items = Vec::new();
-    loop {
-        if let Ok(fn_defn) = munch_fn_defn(&mut cursor) {
-            items.push(Item::FnDefn(fn_defn));
-            continue;
-        }
-        if cursor.is_empty() {
-            break;
+    while !cursor.is_empty() {
+        let start = cursor.idx;
+        let scopes = Rc::clone(&cursor.scopes);
+        match munch_item(&mut cursor) {
+            Ok(mut new_items) => items.append(&mut new_items),
+            Err(err) => {
+                // Forget any scopes the failed item left open
+                cursor.scopes = scopes;
+                cursor.recover(err, start);
+            }
         }
-        bail!("expected EoF");
     }
-    Ok(Program { items })
+    ParseOutput {
+        program: Program { items },
+        errors: cursor.errors.to_vec(),
+        warnings: cursor.warnings.to_vec(),
+    }
+}
+
+/// Turn `typeof` back into an identifier unless GNU extensions are accepted, since only
+/// GNU C reserves it. `__typeof__` stays a keyword either way.
+fn unreserve_typeof(tokens: Vec<Token>, options: &ParseOptions) -> Vec<Token> {
+    if options.gnu_extensions {
+        return tokens;
+    }
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            Token::Kw(Kw::Typeof) => Token::Ident(Kw::Typeof.to_str().to_string()),
+            token => token,
+        })
+        .collect()
+}
+
+/// Parse the token sequence into the AST of a single C expression.
+pub fn parse_expr(tokens: Vec<Token>) -> Result<Expr> {
+    let tokens = unreserve_typeof(tokens, &ParseOptions::default());
+    let mut cursor = TokenCursor::new(&tokens);
+    let expr = munch_expr(&mut cursor)?;
+    if !cursor.is_empty() {
+        bail!(cursor.expected("end of file"));
+    }
+    Ok(expr)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{lex, ArrayTy, BasicTy, FnDefn, Param, PtrTy, StructTy, Ty};
+    use crate::{
+        lex, lex_with_spans, ArrayTy, BasicTy, ConstExpr, Expr, ExprKind, FnDefn, FnTy, Lit, Param,
+        ParamStyle, PtrTy, Span, StmtKind, StructKind, StructTy, Ty, TyKind,
+    };
 
-    use super::{munch_fn_defn, munch_typed_ident, TokenCursor};
+    use super::{
+        munch_block_items, munch_case_stmt, munch_fn_defn, munch_ty_spec, munch_typed_ident, parse,
+        parse_expr, parse_with_options, parse_with_spans, DeclaratorCtx, ParseOptions, TokenCursor,
+    };
 
     #[test]
     fn test_parse_typed_ident() {
         let test_cases = [
-            ("int a", Some((Ty::Basic(BasicTy::Int), String::from("a")))),
+            (
+                "int a",
+                Some((
+                    Ty::new(TyKind::Basic(BasicTy::Int), Span::default()),
+                    String::from("a"),
+                )),
+            ),
             (
                 "unsigned int bebop_123",
-                Some((Ty::Basic(BasicTy::UnsignedInt), String::from("bebop_123"))),
+                Some((
+                    Ty::new(TyKind::Basic(BasicTy::UnsignedInt), Span::default()),
+                    String::from("bebop_123"),
+                )),
             ),
             (
                 "struct my_struct beep",
                 Some((
-                    Ty::Struct(StructTy {
-                        name: String::from("my_struct"),
-                    }),
+                    Ty::new(
+                        TyKind::Struct(StructTy {
+                            kind: StructKind::Struct,
+                            name: String::from("my_struct"),
+                        }),
+                        Span::default(),
+                    ),
                     String::from("beep"),
                 )),
             ),
             (
                 "void *ptr",
                 Some((
-                    Ty::Ptr(PtrTy {
-                        ty: Box::new(Ty::Basic(BasicTy::Void)),
-                    }),
+                    Ty::new(
+                        TyKind::Ptr(PtrTy {
+                            ty: Box::new(Ty::new(TyKind::Basic(BasicTy::Void), Span::default())),
+                        }),
+                        Span::default(),
+                    ),
                     String::from("ptr"),
                 )),
             ),
             (
                 "char ptr[20]",
                 Some((
-                    Ty::Array(ArrayTy {
-                        ty: Box::new(Ty::Basic(BasicTy::Char)),
-                        length: 20,
-                    }),
+                    Ty::new(
+                        TyKind::Array(ArrayTy {
+                            ty: Box::new(Ty::new(TyKind::Basic(BasicTy::Char), Span::default())),
+                            length: Some(Box::new(Expr::new(
+                                ExprKind::Const(ConstExpr { lit: Lit::Int(20) }),
+                                Span::default(),
+                            ))),
+                            quals: Vec::new(),
+                            is_static: false,
+                            is_star: false,
+                        }),
+                        Span::default(),
+                    ),
                     String::from("ptr"),
                 )),
             ),
             ("void ptr[100]", None),
-            ("int ptr[]", None),
+            (
+                "int ptr[]",
+                Some((
+                    Ty::new(
+                        TyKind::Array(ArrayTy {
+                            ty: Box::new(Ty::new(TyKind::Basic(BasicTy::Int), Span::default())),
+                            length: None,
+                            quals: Vec::new(),
+                            is_static: false,
+                            is_star: false,
+                        }),
+                        Span::default(),
+                    ),
+                    String::from("ptr"),
+                )),
+            ),
+            (
+                "int (*fp)(char)",
+                Some((
+                    Ty::new(
+                        TyKind::Ptr(PtrTy {
+                            ty: Box::new(Ty::new(
+                                TyKind::Fn(FnTy {
+                                    ret: Box::new(Ty::new(
+                                        TyKind::Basic(BasicTy::Int),
+                                        Span::default(),
+                                    )),
+                                    params: vec![Param {
+                                        attrs: Vec::new(),
+                                        ident: None,
+                                        ty: Ty::new(TyKind::Basic(BasicTy::Char), Span::default()),
+                                        span: Span::default(),
+                                    }],
+                                    variadic: false,
+                                    style: ParamStyle::Prototype,
+                                }),
+                                Span::default(),
+                            )),
+                        }),
+                        Span::default(),
+                    ),
+                    String::from("fp"),
+                )),
+            ),
+            ("int f(void)[3]", None),
+            ("int *", None),
             ("", None),
         ];
         for (input, expect) in test_cases {
             let tokens = lex(input).unwrap();
             let mut cursor = TokenCursor::new(&tokens);
-            let output = munch_typed_ident(&mut cursor);
-            assert_eq!(output.ok(), expect);
+            let output = munch_ty_spec(&mut cursor)
+                .and_then(|ty| munch_typed_ident(&mut cursor, ty, DeclaratorCtx::Other));
+            assert_eq!(output.ok().map(|(ty, ident, _)| (ty, ident)), expect);
         }
     }
 
@@ -300,47 +3070,117 @@ mod tests {
             (
                 "int main(void)\n{}\n",
                 Some(FnDefn {
+                    attrs: Vec::new(),
+                    storage: None,
+                    is_inline: false,
                     name: String::from("main"),
-                    ret: Ty::Basic(BasicTy::Int),
+                    ret: Ty::new(TyKind::Basic(BasicTy::Int), Span::default()),
                     params: Vec::new(),
+                    variadic: false,
+                    style: ParamStyle::Prototype,
                     stmts: Vec::new(),
                 }),
             ),
             (
                 "int main(char a, char b) {}",
                 Some(FnDefn {
+                    attrs: Vec::new(),
+                    storage: None,
+                    is_inline: false,
                     name: String::from("main"),
-                    ret: Ty::Basic(BasicTy::Int),
+                    ret: Ty::new(TyKind::Basic(BasicTy::Int), Span::default()),
+                    params: vec![
+                        Param {
+                            attrs: Vec::new(),
+                            ident: Some(String::from("a")),
+                            ty: Ty::new(TyKind::Basic(BasicTy::Char), Span::default()),
+                            span: Span::default(),
+                        },
+                        Param {
+                            attrs: Vec::new(),
+                            ident: Some(String::from("b")),
+                            ty: Ty::new(TyKind::Basic(BasicTy::Char), Span::default()),
+                            span: Span::default(),
+                        },
+                    ],
+                    variadic: false,
+                    style: ParamStyle::Prototype,
+                    stmts: Vec::new(),
+                }),
+            ),
+            (
+                "int old(a, b) char *b; {}",
+                Some(FnDefn {
+                    attrs: Vec::new(),
+                    storage: None,
+                    is_inline: false,
+                    name: String::from("old"),
+                    ret: Ty::new(TyKind::Basic(BasicTy::Int), Span::default()),
                     params: vec![
                         Param {
-                            ident: String::from("a"),
-                            ty: Ty::Basic(BasicTy::Char),
+                            attrs: Vec::new(),
+                            ident: Some(String::from("a")),
+                            ty: Ty::new(TyKind::Basic(BasicTy::Int), Span::default()),
+                            span: Span::default(),
                         },
                         Param {
-                            ident: String::from("b"),
-                            ty: Ty::Basic(BasicTy::Char),
+                            attrs: Vec::new(),
+                            ident: Some(String::from("b")),
+                            ty: Ty::new(
+                                TyKind::Ptr(PtrTy {
+                                    ty: Box::new(Ty::new(
+                                        TyKind::Basic(BasicTy::Char),
+                                        Span::default(),
+                                    )),
+                                }),
+                                Span::default(),
+                            ),
+                            span: Span::default(),
                         },
                     ],
+                    variadic: false,
+                    style: ParamStyle::OldStyle,
                     stmts: Vec::new(),
                 }),
             ),
             (
                 "struct my_struct *my_fn(struct my_struct *my_param) {}",
                 Some(FnDefn {
+                    attrs: Vec::new(),
+                    storage: None,
+                    is_inline: false,
                     name: String::from("my_fn"),
-                    ret: Ty::Ptr(PtrTy {
-                        ty: Box::new(Ty::Struct(StructTy {
-                            name: String::from("my_struct"),
-                        })),
-                    }),
-                    params: vec![Param {
-                        ident: String::from("my_param"),
-                        ty: Ty::Ptr(PtrTy {
-                            ty: Box::new(Ty::Struct(StructTy {
-                                name: String::from("my_struct"),
-                            })),
+                    ret: Ty::new(
+                        TyKind::Ptr(PtrTy {
+                            ty: Box::new(Ty::new(
+                                TyKind::Struct(StructTy {
+                                    kind: StructKind::Struct,
+                                    name: String::from("my_struct"),
+                                }),
+                                Span::default(),
+                            )),
                         }),
+                        Span::default(),
+                    ),
+                    params: vec![Param {
+                        attrs: Vec::new(),
+                        ident: Some(String::from("my_param")),
+                        ty: Ty::new(
+                            TyKind::Ptr(PtrTy {
+                                ty: Box::new(Ty::new(
+                                    TyKind::Struct(StructTy {
+                                        kind: StructKind::Struct,
+                                        name: String::from("my_struct"),
+                                    }),
+                                    Span::default(),
+                                )),
+                            }),
+                            Span::default(),
+                        ),
+                        span: Span::default(),
                     }],
+                    variadic: false,
+                    style: ParamStyle::Prototype,
                     stmts: Vec::new(),
                 }),
             ),
@@ -353,4 +3193,244 @@ mod tests {
             assert_eq!(output.ok(), expect);
         }
     }
+
+    #[test]
+    fn test_parse_case_range() {
+        let tokens = lex("case 1 ... 5: ;").unwrap();
+        let mut cursor = TokenCursor::new(&tokens);
+        let output = munch_case_stmt(&mut cursor);
+        assert_eq!(
+            output.unwrap_err().to_string(),
+            "case ranges are a GNU extension"
+        );
+
+        let options = ParseOptions {
+            gnu_extensions: true,
+        };
+        let mut cursor = TokenCursor::with_options(&tokens, &[], options);
+        let output = munch_case_stmt(&mut cursor).unwrap();
+        assert!(output.expr_end.is_some());
+        assert!(cursor.is_empty());
+    }
+
+    #[test]
+    fn test_parse_basic_ty() {
+        let test_cases = [
+            ("char", Ok(BasicTy::Char)),
+            ("signed char", Ok(BasicTy::SignedChar)),
+            ("char unsigned", Ok(BasicTy::UnsignedChar)),
+            ("short int", Ok(BasicTy::Short)),
+            ("unsigned short", Ok(BasicTy::UnsignedShort)),
+            ("signed", Ok(BasicTy::Int)),
+            ("int signed", Ok(BasicTy::Int)),
+            ("long unsigned int", Ok(BasicTy::UnsignedLong)),
+            ("int long", Ok(BasicTy::Long)),
+            ("long int long", Ok(BasicTy::LongLong)),
+            ("unsigned long long", Ok(BasicTy::UnsignedLongLong)),
+            ("unsigned __int128", Ok(BasicTy::UnsignedInt128)),
+            ("_Bool", Ok(BasicTy::Bool)),
+            ("float", Ok(BasicTy::Float)),
+            ("long double", Ok(BasicTy::LongDouble)),
+            ("short long", Err("invalid type specifiers `short long`")),
+            (
+                "long long long",
+                Err("invalid type specifiers `long long long`"),
+            ),
+            (
+                "signed unsigned int",
+                Err("invalid type specifiers `signed unsigned int`"),
+            ),
+            (
+                "unsigned double",
+                Err("invalid type specifiers `unsigned double`"),
+            ),
+            ("void int", Err("invalid type specifiers `void int`")),
+        ];
+        for (input, expect) in test_cases {
+            let tokens = lex(input).unwrap();
+            let mut cursor = TokenCursor::new(&tokens);
+            let output = munch_ty_spec(&mut cursor).map_err(|err| err.to_string());
+            assert_eq!(
+                output,
+                expect
+                    .map(|ty| Ty::new(TyKind::Basic(ty), Span::default()))
+                    .map_err(String::from)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_basic_ty_any_order() {
+        let test_cases = [
+            (
+                vec!["unsigned", "long", "long", "int"],
+                BasicTy::UnsignedLongLong,
+            ),
+            (vec!["signed", "short", "int"], BasicTy::Short),
+            (vec!["unsigned", "char"], BasicTy::UnsignedChar),
+            (vec!["signed", "__int128"], BasicTy::Int128),
+            (vec!["long", "double"], BasicTy::LongDouble),
+        ];
+        for (kws, expect) in test_cases {
+            // Build every ordering of the keywords by inserting each one at every position
+            let mut orders = vec![Vec::new()];
+            for kw in kws {
+                orders = orders
+                    .into_iter()
+                    .flat_map(|order: Vec<&str>| {
+                        (0..=order.len()).map(move |idx| {
+                            let mut order = order.clone();
+                            order.insert(idx, kw);
+                            order
+                        })
+                    })
+                    .collect();
+            }
+            for order in orders {
+                let input = order.join(" ");
+                let tokens = lex(&input).unwrap();
+                let mut cursor = TokenCursor::new(&tokens);
+                let ty = munch_ty_spec(&mut cursor).unwrap();
+                assert_eq!(ty.kind, TyKind::Basic(expect.clone()), "{input}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_decl_specs() {
+        let test_cases = [
+            (
+                "{ extern register int x; }",
+                "cannot combine `extern` with `register`",
+            ),
+            (
+                "{ static int static x; }",
+                "cannot combine `static` with `static`",
+            ),
+            (
+                "{ int struct s x; }",
+                "cannot combine `struct` with previous type",
+            ),
+            ("{ restrict int *x; }", "`restrict` requires a pointer type"),
+            ("{ inline int x; }", "`inline` can only appear on functions"),
+            (
+                "{ for (static int i;;) {} }",
+                "for loop can only declare `auto` or `register` variables",
+            ),
+            ("{ typedef int T = 1; }", "typedef cannot be initialized"),
+            ("{ int f(void) = 0; }", "function cannot be initialized"),
+            ("{ register int f(void); }", "function cannot be `register`"),
+        ];
+        for (input, expect) in test_cases {
+            let tokens = lex(input).unwrap();
+            let mut cursor = TokenCursor::new(&tokens);
+            let output = munch_block_items(&mut cursor);
+            // The block recovers from the error, leaving nothing of the bad item behind
+            assert_eq!(output.unwrap(), vec![]);
+            assert!(cursor.is_empty());
+            let errors = cursor.errors.iter().map(|err| err.to_string());
+            assert_eq!(errors.collect::<Vec<_>>(), [expect]);
+        }
+
+        let tokens = lex("int main(static int argc) {}").unwrap();
+        let mut cursor = TokenCursor::new(&tokens);
+        let output = munch_fn_defn(&mut cursor);
+        assert_eq!(
+            output.unwrap_err().to_string(),
+            "parameter cannot be `static`"
+        );
+
+        let tokens = lex("register int main(void) {}").unwrap();
+        let mut cursor = TokenCursor::new(&tokens);
+        let output = munch_fn_defn(&mut cursor);
+        assert_eq!(
+            output.unwrap_err().to_string(),
+            "function cannot be `register`"
+        );
+
+        let tokens = lex("auto int x;").unwrap();
+        let output = parse(tokens);
+        assert_eq!(
+            output.unwrap_err().to_string(),
+            "file-scope variable cannot be `auto`"
+        );
+    }
+
+    #[test]
+    fn test_parse_typedef_scope() {
+        let tokens = lex("{ typedef int T; T x; { int T; } T y; }").unwrap();
+        let mut cursor = TokenCursor::new(&tokens);
+        assert!(!cursor.is_typedef("T"));
+        munch_block_items(&mut cursor).unwrap();
+        // The typedef ends with the block that declared it
+        assert!(!cursor.is_typedef("T"));
+
+        let tokens = lex("{ typedef int T; { int T; T * x; } }").unwrap();
+        let mut cursor = TokenCursor::new(&tokens);
+        let output = munch_block_items(&mut cursor).unwrap();
+        // `T` is shadowed by a variable, so `T * x` is a multiplication
+        let StmtKind::Block(block) = &output[1].kind else {
+            panic!("expected block");
+        };
+        assert!(matches!(block.stmts[1].kind, StmtKind::Expr(_)));
+    }
+
+    #[test]
+    fn test_parse_nested_declarator() {
+        // Each level of parentheses must be parsed once, or this takes exponential time
+        let depth = 64;
+        let input = format!("int {}*x{}", "(".repeat(depth), ")".repeat(depth));
+        let tokens = lex(&input).unwrap();
+        let mut cursor = TokenCursor::new(&tokens);
+        let ty = munch_ty_spec(&mut cursor).unwrap();
+        let (ty, ident, _) = munch_typed_ident(&mut cursor, ty, DeclaratorCtx::Other).unwrap();
+        assert_eq!(ident, "x");
+        assert!(matches!(ty.kind, TyKind::Ptr(_)));
+        assert!(cursor.is_empty());
+    }
+
+    #[test]
+    fn test_parse_nested_ty_name() {
+        // Each parenthesized type name must be parsed once, or this takes exponential time
+        let depth = 16;
+        let wrappers: [fn(String) -> String; 3] = [
+            |expr| format!("(struct {{ int m[{expr}]; }} *)0"),
+            |expr| format!("sizeof(struct {{ int m[{expr}]; }})"),
+            |expr| format!("(struct {{ int m[{expr}]; }}){{ 0 }}.m[0]"),
+        ];
+        for wrap in wrappers {
+            let input = (0..depth).fold(String::from("1"), |expr, _| wrap(expr));
+            let tokens = lex(&input).unwrap();
+            assert!(parse_expr(tokens).is_ok(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_parse_nested_stmt_expr() {
+        // Block items must not be parsed ahead of time, or this takes exponential time
+        let depth = 24;
+        let expr = (0..depth).fold(String::from("0"), |expr, _| {
+            format!("({{ typeof({expr}) a; a; }})")
+        });
+        let input = format!("int f(void) {{ return {expr}; }}");
+        let tokens = lex(&input).unwrap();
+        let options = ParseOptions {
+            gnu_extensions: true,
+        };
+        assert!(parse_with_options(tokens, options).is_ok());
+    }
+
+    #[test]
+    fn test_recover_without_spans() {
+        // Recovery only looks at the tokens, so spans must not change what is reported
+        let input = "int f(void) {\n    x;\nint g(void) { return 0; }";
+        let (tokens, spans) = lex_with_spans(input).unwrap();
+        let outputs = [spans, Vec::new()]
+            .map(|spans| parse_with_spans(tokens.clone(), spans, ParseOptions::default()));
+        for output in outputs {
+            assert_eq!(output.program.items.len(), 2);
+            let errors = output.errors.iter().map(|err| err.message.as_str());
+            assert_eq!(errors.collect::<Vec<_>>(), ["expected `}`, found `int`"]);
+        }
+    }
 }
@@ -1,14 +1,60 @@
+use crate::Span;
 use std::fmt::{self, Write};
 
+/// Options controlling what `PrettyPrint` includes in its output
+#[derive(Debug, Clone, Default)]
+pub struct PrettyOptions {
+    /// Print the position each node starts at as `@line:col`
+    pub spans: bool,
+}
+
+/// The buffer that pretty printed output is written to
+pub struct PrettyWriter {
+    buffer: String,
+    options: PrettyOptions,
+}
+
+impl PrettyWriter {
+    /// Create an empty writer that prints with the given options
+    pub fn new(options: PrettyOptions) -> Self {
+        PrettyWriter {
+            buffer: String::new(),
+            options,
+        }
+    }
+
+    /// Write the position of a node as ` @line:col`, if enabled by the options
+    pub fn span(&mut self, span: Span) -> fmt::Result {
+        if self.options.spans {
+            write!(self, " @{}", span.start)?;
+        }
+        Ok(())
+    }
+
+    /// Returns everything written so far
+    pub fn finish(self) -> String {
+        self.buffer
+    }
+}
+
+impl Write for PrettyWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.buffer.write_str(s)
+    }
+}
+
 pub trait PrettyPrint {
-    fn pretty_fmt(&self, w: &mut impl Write, depth: usize) -> fmt::Result;
+    fn pretty_fmt(&self, w: &mut PrettyWriter, depth: usize) -> fmt::Result;
     fn pretty_print(&self) -> String {
-        let mut buffer = String::new();
-        self.pretty_fmt(&mut buffer, 0).unwrap();
-        buffer
+        self.pretty_print_with(PrettyOptions::default())
+    }
+    fn pretty_print_with(&self, options: PrettyOptions) -> String {
+        let mut writer = PrettyWriter::new(options);
+        self.pretty_fmt(&mut writer, 0).unwrap();
+        writer.finish()
     }
 }
-pub(crate) const INDENT: &'static str = " ";
+pub(crate) const INDENT: &str = " ";
 
 pub(crate) fn indent(depth: usize) -> String {
     INDENT.repeat(depth)
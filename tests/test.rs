@@ -1,5 +1,7 @@
 use anyhow::{anyhow, Result};
-use bcc::{lex, parse, PrettyPrint};
+use bcc::{
+    lex, lex_with_spans, parse_expr, parse_with_spans, ParseOptions, PrettyOptions, PrettyPrint,
+};
 use colored::Colorize;
 use similar::{ChangeTag, TextDiff};
 use std::{
@@ -89,11 +91,30 @@ where
     failures
 }
 
+/// Parse a whole program, printing what could be parsed followed by any warnings and errors
+fn parse_program(input: &str, options: ParseOptions, pretty: PrettyOptions) -> Result<String> {
+    let (tokens, spans) = match lex_with_spans(input) {
+        Ok(x) => x,
+        Err(err) => {
+            return Err(err.context("lex error"));
+        }
+    };
+    let output = parse_with_spans(tokens, spans, options);
+    let mut result = output.program.pretty_print_with(pretty);
+    for warning in output.warnings {
+        writeln!(result, "warning: {warning}").unwrap();
+    }
+    for err in output.errors {
+        writeln!(result, "error: {err}").unwrap();
+    }
+    Ok(result)
+}
+
 #[test]
 fn run_all_tests() -> Result<()> {
     let mut failures = 0;
     failures += run_suite("lex", |input| {
-        let tokens = match lex(&input) {
+        let tokens = match lex(input) {
             Ok(x) => x,
             Err(err) => {
                 return Ok(err.to_string());
@@ -106,19 +127,35 @@ fn run_all_tests() -> Result<()> {
         Ok(output)
     });
     failures += run_suite("parse", |input| {
-        let tokens = match lex(&input) {
+        parse_program(input, ParseOptions::default(), PrettyOptions::default())
+    });
+    failures += run_suite("gnu", |input| {
+        let options = ParseOptions {
+            gnu_extensions: true,
+        };
+        parse_program(input, options, PrettyOptions::default())
+    });
+    failures += run_suite("spans", |input| {
+        parse_program(
+            input,
+            ParseOptions::default(),
+            PrettyOptions { spans: true },
+        )
+    });
+    failures += run_suite("expr", |input| {
+        let tokens = match lex(input) {
             Ok(x) => x,
             Err(err) => {
                 return Err(err.context("lex error"));
             }
         };
-        let ast = match parse(tokens) {
+        let expr = match parse_expr(tokens) {
             Ok(x) => x,
             Err(err) => {
                 return Ok(err.to_string());
             }
         };
-        Ok(ast.pretty_print())
+        Ok(expr.pretty_print())
     });
 
     if failures > 0 {